@@ -0,0 +1,288 @@
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+// a complex number re + i*im, just the things the solvers need, so no dependencies are pulled in
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl Complex {
+    pub const ZERO: Complex = Complex{re: 0.0, im: 0.0};
+    pub const ONE: Complex = Complex{re: 1.0, im: 0.0};
+    pub const I: Complex = Complex{re: 0.0, im: 1.0};
+    pub const NAN: Complex = Complex{re: f64::NAN, im: f64::NAN};
+
+    pub fn new(re: f64, im: f64) -> Complex {
+        Complex{re, im}
+    }
+
+    // the point at distance r from the origin with angle theta
+    pub fn from_polar(r: f64, theta: f64) -> Complex {
+        Complex{re: r*theta.cos(), im: r*theta.sin()}
+    }
+
+    pub fn abs(self) -> f64 {
+        self.re.hypot(self.im)
+    }
+
+    pub fn norm_sqr(self) -> f64 {
+        self.re*self.re + self.im*self.im
+    }
+
+    pub fn arg(self) -> f64 {
+        self.im.atan2(self.re)
+    }
+
+    pub fn conj(self) -> Complex {
+        Complex{re: self.re, im: -self.im}
+    }
+
+    pub fn is_finite(self) -> bool {
+        self.re.is_finite() && self.im.is_finite()
+    }
+
+    pub fn exp(self) -> Complex {
+        Complex::from_polar(self.re.exp(), self.im)
+    }
+
+    // the principal logarithm, with the imaginary part in (-pi, pi]
+    pub fn ln(self) -> Complex {
+        Complex{re: self.abs().ln(), im: self.arg()}
+    }
+
+    pub fn sin(self) -> Complex {
+        Complex{re: self.re.sin()*self.im.cosh(), im: self.re.cos()*self.im.sinh()}
+    }
+
+    pub fn cos(self) -> Complex {
+        Complex{re: self.re.cos()*self.im.cosh(), im: -self.re.sin()*self.im.sinh()}
+    }
+
+    // the principal square root, the one with a non negative real part
+    pub fn sqrt(self) -> Complex {
+        let r = self.abs();
+        let re = ((r + self.re)/2.0).sqrt();
+        let im = ((r - self.re)/2.0).sqrt();
+
+        Complex{re, im: if self.im.is_sign_negative() { -im } else { im }}
+    }
+
+    pub fn powi(self, n: i32) -> Complex {
+        let mut result = Complex::ONE;
+        let mut base = if n < 0 { Complex::ONE/self } else { self };
+        let mut n = n.unsigned_abs();
+
+        while n > 0 {
+            if n & 1 == 1 {
+                result *= base;
+            }
+            base *= base;
+            n >>= 1;
+        }
+
+        result
+    }
+}
+
+impl From<f64> for Complex {
+    fn from(re: f64) -> Complex {
+        Complex{re, im: 0.0}
+    }
+}
+
+impl fmt::Display for Complex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.im < 0.0 {
+            write!(f, "{}-{}i", self.re, -self.im)
+        } else {
+            write!(f, "{}+{}i", self.re, self.im)
+        }
+    }
+}
+
+impl Neg for Complex {
+    type Output = Complex;
+
+    fn neg(self) -> Complex {
+        Complex{re: -self.re, im: -self.im}
+    }
+}
+
+impl Add for Complex {
+    type Output = Complex;
+
+    fn add(self, other: Complex) -> Complex {
+        Complex{re: self.re + other.re, im: self.im + other.im}
+    }
+}
+
+impl Sub for Complex {
+    type Output = Complex;
+
+    fn sub(self, other: Complex) -> Complex {
+        Complex{re: self.re - other.re, im: self.im - other.im}
+    }
+}
+
+impl Mul for Complex {
+    type Output = Complex;
+
+    fn mul(self, other: Complex) -> Complex {
+        Complex{
+            re: self.re*other.re - self.im*other.im,
+            im: self.re*other.im + self.im*other.re,
+        }
+    }
+}
+
+// smith's algorithm, dividing by |other|^2 directly overflows way before it should
+impl Div for Complex {
+    type Output = Complex;
+
+    fn div(self, other: Complex) -> Complex {
+        if other.re.abs() >= other.im.abs() {
+            let r = other.im/other.re;
+            let d = other.re + other.im*r;
+            Complex{re: (self.re + self.im*r)/d, im: (self.im - self.re*r)/d}
+        } else {
+            let r = other.re/other.im;
+            let d = other.re*r + other.im;
+            Complex{re: (self.re*r + self.im)/d, im: (self.im*r - self.re)/d}
+        }
+    }
+}
+
+// the mixed operations with f64 on both sides, and the assign versions of everything
+macro_rules! impl_complex_ops {
+    ($($trait:ident $method:ident $assign_trait:ident $assign_method:ident),*) => {$(
+        impl $trait<f64> for Complex {
+            type Output = Complex;
+
+            fn $method(self, other: f64) -> Complex {
+                $trait::$method(self, Complex::from(other))
+            }
+        }
+
+        impl $trait<Complex> for f64 {
+            type Output = Complex;
+
+            fn $method(self, other: Complex) -> Complex {
+                $trait::$method(Complex::from(self), other)
+            }
+        }
+
+        impl $assign_trait for Complex {
+            fn $assign_method(&mut self, other: Complex) {
+                *self = $trait::$method(*self, other);
+            }
+        }
+
+        impl $assign_trait<f64> for Complex {
+            fn $assign_method(&mut self, other: f64) {
+                *self = $trait::$method(*self, other);
+            }
+        }
+    )*};
+}
+
+impl_complex_ops!(
+    Add add AddAssign add_assign,
+    Sub sub SubAssign sub_assign,
+    Mul mul MulAssign mul_assign,
+    Div div DivAssign div_assign
+);
+
+//--------------------------------------------------------------------------------------------------
+//
+// CRATE TESTS
+//
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_complex_arithmetic() {
+        struct Test {
+            result: Complex,
+            expect: Complex,
+        }
+
+        let a = Complex::new(3.0, 4.0);
+        let b = Complex::new(1.0, -2.0);
+
+        let tests = vec![
+            Test{
+                result: a + b,
+                expect: Complex::new(4.0, 2.0),
+            },
+            Test{
+                result: a - b,
+                expect: Complex::new(2.0, 6.0),
+            },
+            Test{
+                result: a*b,
+                expect: Complex::new(11.0, -2.0),
+            },
+            Test{
+                result: a/b,
+                expect: Complex::new(-1.0, 2.0),
+            },
+            Test{
+                result: 2.0*a - 1.0,
+                expect: Complex::new(5.0, 8.0),
+            },
+            Test{
+                result: a.sqrt(),
+                expect: Complex::new(2.0, 1.0),
+            },
+            Test{
+                result: Complex::new(-4.0, -0.0).sqrt(),
+                expect: Complex::new(0.0, -2.0),
+            },
+            Test{
+                result: a.powi(3),
+                expect: a*a*a,
+            },
+            Test{
+                result: a.powi(-2)*a*a,
+                expect: Complex::ONE,
+            },
+            Test{
+                result: (Complex::I*std::f64::consts::PI).exp(),
+                expect: Complex::new(-1.0, 0.0),
+            },
+            Test{
+                result: Complex::new(1.0e300, 1.0e300)/Complex::new(1.0e300, 1.0e300),
+                expect: Complex::ONE,
+            },
+            Test{
+                result: a.ln().exp(),
+                expect: a,
+            },
+            Test{
+                result: Complex::new(-1.0, 0.0).ln(),
+                expect: Complex::new(0.0, std::f64::consts::PI),
+            },
+            Test{
+                result: b.sin()*b.sin() + b.cos()*b.cos(),
+                expect: Complex::ONE,
+            },
+            Test{
+                result: (Complex::I*1.0).sin(),
+                expect: Complex::new(0.0, 1.0_f64.sinh()),
+            },
+        ];
+
+        for test in tests {
+            assert!((test.result - test.expect).abs() < 1.0e-14, "{} {}", test.result, test.expect);
+        }
+
+        assert_eq!(a.abs(), 5.0);
+        assert_eq!(a.conj(), Complex::new(3.0, -4.0));
+        assert_eq!(format!("{}", b), "1-2i");
+    }
+}
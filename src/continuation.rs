@@ -0,0 +1,289 @@
+use crate::*;
+
+// how many times arclength_continuation halves its step when the corrector fails before giving up
+const MAX_STEP_HALVINGS: usize = 10;
+
+// follows the root x*(p) of f(x, p) = 0 as p goes from pmin to pmax in num_steps equal steps, each
+// root solved with newton_root starting from the previous one (and the first one from xo). Returns
+// the branch as (p, x*) pairs, also written to the file if one is given. At a fold (a turning point,
+// where the branch turns back in p) there is no root past it on the same branch, newton either
+// fails or lands on some other branch. The branch stops at the last root before the fold in both
+// cases, use arclength_continuation to get around it. A jump to another branch is told apart with
+// the slopes dx*/dp of the branch at both ends of the step: along a branch the change of x* is the
+// slope somewhere in between times the step in p, so it has to be close to the range of the two
+// predicted changes, a jump usually lands far outside of it
+pub fn natural_continuation<F, O>(mut fx: F, xo: f64, pmin: f64, pmax: f64, num_steps: usize, options: O, mut file: Option<&mut DataFile>) -> Vec<(f64, f64)>
+where
+    F: FnMut(f64, f64) -> f64,
+    O: Into<SolverOptions>,
+{
+    let options = options.into();
+    let mut branch = Vec::with_capacity(num_steps + 1);
+    let mut x = xo;
+    let mut previous: Option<(f64, f64)> = None;
+
+    for i in 0..=num_steps {
+        let p = pmin + (pmax - pmin)*i as f64/num_steps.max(1) as f64;
+
+        let root = match try_newton_root(|x| fx(x, p), x, options) {
+            Ok(report) => report.root,
+            Err(_) => break,
+        };
+
+        let slope = branch_slope(&mut fx, root, p);
+        if let Some((pp, slope_pp)) = previous {
+            if jumped(root - x, (p - pp)*slope_pp, (p - pp)*slope, root) {
+                break;
+            }
+        }
+        previous = Some((p, slope));
+        x = root;
+
+        if let Some(file) = file.as_deref_mut() {
+            file.write(p, x);
+        }
+        branch.push((p, x));
+    }
+
+    branch
+}
+
+// follows the branch of roots of f(x, p) = 0 through (po, xo) with pseudo-arclength continuation,
+// taking num_steps steps of length ds along the branch itself instead of along p, so it gets around
+// folds where natural_continuation stops. Each step goes ds along the tangent of the branch (the
+// predictor) and then back to the branch with newton_system on f(x, p) = 0 plus the condition that
+// the correction is orthogonal to the tangent (the corrector). The tangent keeps its orientation
+// from one step to the next, so the branch is followed through the fold with p going back. A
+// positive ds starts towards increasing p. If the corrector fails the step is halved, and the
+// branch ends if it still fails after 10 halvings. Returns the branch as (p, x*) pairs, also written
+// to the file if one is given
+pub fn arclength_continuation<F, O>(mut fx: F, xo: f64, po: f64, ds: f64, num_steps: usize, options: O, mut file: Option<&mut DataFile>) -> Vec<(f64, f64)>
+where
+    F: FnMut(f64, f64) -> f64,
+    O: Into<SolverOptions>,
+{
+    let options = options.into();
+    let mut branch = Vec::with_capacity(num_steps + 1);
+
+    let mut x = match try_newton_root(|x| fx(x, po), xo, options) {
+        Ok(report) => report.root,
+        Err(_) => return branch,
+    };
+    let mut p = po;
+
+    if let Some(file) = file.as_deref_mut() {
+        file.write(p, x);
+    }
+    branch.push((p, x));
+
+    let mut tangent = match branch_tangent(&mut fx, x, p) {
+        Some((tx, tp)) if tp < 0.0 || (tp == 0.0 && tx < 0.0) => (-tx*ds.signum(), -tp*ds.signum()),
+        Some((tx, tp)) => (tx*ds.signum(), tp*ds.signum()),
+        None => return branch,
+    };
+    let ds = ds.abs();
+
+    for _ in 0..num_steps {
+        let mut step = ds;
+        let mut corrected = None;
+
+        for _ in 0..=MAX_STEP_HALVINGS {
+            let (xp, pp) = (x + step*tangent.0, p + step*tangent.1);
+
+            let result = try_newton_system(|v: &[f64]| {
+                vec![fx(v[0], v[1]), tangent.0*(v[0] - xp) + tangent.1*(v[1] - pp)]
+            }, &[xp, pp], options);
+
+            if let Ok(report) = result {
+                corrected = Some((report.root[0], report.root[1]));
+                break;
+            }
+            step /= 2.0;
+        }
+
+        let Some((xn, pn)) = corrected else {
+            break;
+        };
+
+        // the new tangent points the same way as the old one, that's what turns p around at a fold
+        tangent = match branch_tangent(&mut fx, xn, pn) {
+            Some((tx, tp)) if tx*tangent.0 + tp*tangent.1 < 0.0 => (-tx, -tp),
+            Some(t) => t,
+            None => break,
+        };
+        (x, p) = (xn, pn);
+
+        if let Some(file) = file.as_deref_mut() {
+            file.write(p, x);
+        }
+        branch.push((p, x));
+    }
+
+    branch
+}
+
+// the slope dx*/dp of the branch through (x, p), infinite at a fold and NaN where the branch isn't
+// a curve
+fn branch_slope<F>(fx: &mut F, x: f64, p: f64) -> f64
+where
+    F: FnMut(f64, f64) -> f64,
+{
+    match branch_tangent(fx, x, p) {
+        Some((tx, tp)) => tx/tp,
+        None => f64::NAN,
+    }
+}
+
+// true if a step of natural_continuation that changed x* by dx, where the slopes at both ends
+// predicted a change of da and db, went to another branch. By the mean value theorem dx is the
+// predicted change somewhere in between, so when the slope doesn't turn around within the step it
+// is between da and db. Some slack is given for the slopes that do, and for the error of the
+// estimated derivatives and of the roots themselves
+fn jumped(dx: f64, da: f64, db: f64, x: f64) -> bool {
+    let slack = (da - db).abs()/2.0 + 0.1*da.abs().max(db.abs()) + f64::EPSILON.sqrt()*x.abs().max(1.0);
+    dx < da.min(db) - slack || dx > da.max(db) + slack
+}
+
+// the unit tangent (dx, dp) of the branch f(x, p) = 0 at (x, p), which is orthogonal to the
+// gradient (f_x, f_p). None where the gradient vanishes, since the branch isn't a curve there
+fn branch_tangent<F>(fx: &mut F, x: f64, p: f64) -> Option<(f64, f64)>
+where
+    F: FnMut(f64, f64) -> f64,
+{
+    let dfdx = calculate_derivative(|x| fx(x, p), x);
+    let dfdp = calculate_derivative(|p| fx(x, p), p);
+    let norm = dfdx.hypot(dfdp);
+
+    if norm == 0.0 || !norm.is_finite() {
+        return None;
+    }
+
+    Some((-dfdp/norm, dfdx/norm))
+}
+
+//--------------------------------------------------------------------------------------------------
+//
+// CRATE TESTS
+//
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_natural_continuation() {
+        // x = cos(p x) has a single smooth branch from p = 0 to p = 1
+        let branch = natural_continuation(|x, p| x - (p*x).cos(), 1.0, 0.0, 1.0, 10, None, None);
+        assert_eq!(branch.len(), 11);
+        assert_eq!(branch[0], (0.0, 1.0));
+
+        for (i, (p, x)) in branch.into_iter().enumerate() {
+            assert!((p - i as f64/10.0).abs() < 1.0e-15);
+            assert!((x - (p*x).cos()).abs() < 1.0e-14);
+        }
+
+        // x^2 + p = 0 folds at p = 0, there are no roots past it
+        let branch = natural_continuation(|x, p| x.powf(2.0) + p, 1.0, -1.0, 1.0, 20, None, None);
+        assert_eq!(branch.len(), 11);
+        assert!(branch.iter().all(|&(p, x)| p <= 0.0 && (x - (-p).sqrt()).abs() < 1.0e-7));
+
+        // past the fold of x^3 - x = p at p = 2/(3 sqrt(3)) newton finds the roots of the upper
+        // branch, which is a jump and not where the lower branch goes
+        let fold = 2.0/(3.0*3.0_f64.sqrt());
+        for num_steps in [15, 30, 60, 100] {
+            let branch = natural_continuation(|x, p| x.powf(3.0) - x - p, -1.5, -1.875, 1.875, num_steps, None, None);
+            let dp = 3.75/num_steps as f64;
+            let (p, _) = *branch.last().unwrap();
+
+            assert!(branch.iter().all(|&(_, x)| x < -1.0/3.0_f64.sqrt()), "{:?}", branch);
+            assert!(p <= fold && p > fold - dp, "{} {}", num_steps, p);
+        }
+
+        // and the same coming from the upper branch, which folds back at p = -2/(3 sqrt(3))
+        let branch = natural_continuation(|x, p| x.powf(3.0) - x - p, 1.5, 1.875, -1.875, 30, None, None);
+        assert!(branch.iter().all(|&(_, x)| x > 1.0/3.0_f64.sqrt()));
+        assert!(branch.last().unwrap().0 >= -fold);
+    }
+
+    #[test]
+    fn test_arclength_continuation() {
+        struct Test {
+            fx: fn(f64, f64) -> f64,
+            xo: f64,
+            po: f64,
+            ds: f64,
+            num_steps: usize,
+            // where p turns back, and where the last root has to end up
+            folds: Vec<f64>,
+            last_x: (f64, f64),
+        }
+
+        let tests = vec![
+            Test{
+                // the fold at p = 0, coming back on the negative roots
+                fx: |x, p| {x.powf(2.0) + p},
+                xo: 1.0,
+                po: -1.0,
+                ds: 0.1,
+                num_steps: 40,
+                folds: vec![0.0],
+                last_x: (f64::NEG_INFINITY, -1.0),
+            },
+            Test{
+                // the s shaped branch of x^3 - x = p, with folds at p = +-2/(3 sqrt(3)), going
+                // forward, back and forward again in p
+                fx: |x, p| {x.powf(3.0) - x - p},
+                xo: -1.5,
+                po: -1.875,
+                ds: 0.05,
+                num_steps: 120,
+                folds: vec![2.0/(3.0*3.0_f64.sqrt()), -2.0/(3.0*3.0_f64.sqrt())],
+                last_x: (1.0, f64::INFINITY),
+            },
+        ];
+
+        for test in tests {
+            let branch = arclength_continuation(test.fx, test.xo, test.po, test.ds, test.num_steps, None, None);
+            assert_eq!(branch.len(), test.num_steps + 1);
+
+            for &(p, x) in &branch {
+                assert!((test.fx)(x, p).abs() < 1.0e-10);
+            }
+
+            // the steps along the branch are all about ds long, even around the folds
+            for (a, b) in branch.iter().zip(&branch[1..]) {
+                let length = (b.0 - a.0).hypot(b.1 - a.1);
+                assert!(length > 0.9*test.ds && length < 1.1*test.ds);
+            }
+
+            let turns: Vec<f64> = branch.windows(3)
+                .filter(|w| (w[1].0 - w[0].0)*(w[2].0 - w[1].0) < 0.0)
+                .map(|w| w[1].0)
+                .collect();
+            assert_eq!(turns.len(), test.folds.len(), "{:?}", turns);
+
+            for (turn, fold) in turns.into_iter().zip(test.folds) {
+                assert!((turn - fold).abs() < test.ds*test.ds, "{} {}", turn, fold);
+            }
+
+            let x = branch.last().unwrap().1;
+            assert!(test.last_x.0 < x && x < test.last_x.1);
+        }
+
+        // going the other way from the same point
+        let branch = arclength_continuation(|x, p| x.powf(2.0) + p, 1.0, -1.0, -0.1, 10, None, None);
+        assert!(branch.iter().all(|&(p, x)| p <= -1.0 && x >= 1.0));
+
+        // and the branch written to a file
+        let path = std::env::temp_dir().join(format!("numeric_calc_test_branch_{}.dat", std::process::id()));
+        let mut file = DataFile::create(path.to_str().unwrap());
+        let branch = arclength_continuation(|x, p| x.powf(2.0) + p, 1.0, -1.0, 0.1, 30, None, Some(&mut file));
+        drop(file);
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(contents.lines().count(), branch.len());
+        assert_eq!(contents.lines().next().unwrap(), " -1 1");
+    }
+}
@@ -0,0 +1,473 @@
+use std::f64::consts::PI;
+
+use crate::*;
+
+// a closed curve in the complex plane, walked counterclockwise. The rectangle goes from the corner
+// min (smallest real and imaginary parts) to the corner max
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Contour {
+    Rectangle{min: Complex, max: Complex},
+    Circle{center: Complex, radius: f64},
+}
+
+impl Contour {
+    // the point of the contour at t, going once around it as t goes from 0 to 1
+    pub fn point(&self, t: f64) -> Complex {
+        match *self {
+            Contour::Rectangle{min, max} => {
+                let (w, h) = (max.re - min.re, max.im - min.im);
+                let s = 4.0*t;
+
+                match s as usize {
+                    0 => Complex::new(min.re + s*w, min.im),
+                    1 => Complex::new(max.re, min.im + (s - 1.0)*h),
+                    2 => Complex::new(max.re - (s - 2.0)*w, max.im),
+                    _ => Complex::new(min.re, max.im - (s - 3.0).min(1.0)*h),
+                }
+            },
+            Contour::Circle{center, radius} => center + Complex::from_polar(radius, 2.0*PI*t),
+        }
+    }
+
+    // true if z is strictly inside the contour
+    pub fn contains(&self, z: Complex) -> bool {
+        match *self {
+            Contour::Rectangle{min, max} => min.re < z.re && z.re < max.re && min.im < z.im && z.im < max.im,
+            Contour::Circle{center, radius} => (z - center).abs() < radius,
+        }
+    }
+}
+
+// a straight or circular piece of the boundary of a region, walked from t = 0 to t = 1
+#[derive(Debug, Clone, Copy)]
+enum Segment {
+    Line(Complex, Complex),
+    Arc{center: Complex, radius: f64, from: f64, to: f64},
+}
+
+impl Segment {
+    fn point(&self, t: f64) -> Complex {
+        match *self {
+            Segment::Line(a, b) => a + (b - a)*t,
+            Segment::Arc{center, radius, from, to} => center + Complex::from_polar(radius, from + (to - from)*t),
+        }
+    }
+}
+
+// a piece of the inside of a contour, which is what contour_zeros splits it into. A circle is split
+// in polar coordinates, into a smaller disk and sectors of rings around its center, so the pieces
+// never leave it and whatever the function does outside the circle can't get in the way. Polar
+// holds the points between r.0 and r.1 away from the center, with an angle between theta.0 and
+// theta.1, a full ring when theta is None, and a disk when r.0 is 0 too
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Region {
+    Rectangle{min: Complex, max: Complex},
+    Polar{center: Complex, r: (f64, f64), theta: Option<(f64, f64)>},
+}
+
+impl Region {
+    fn of(contour: Contour) -> Region {
+        match contour {
+            Contour::Rectangle{min, max} => Region::Rectangle{min, max},
+            Contour::Circle{center, radius} => Region::Polar{center, r: (0.0, radius), theta: None},
+        }
+    }
+
+    // the boundary of the region, walked counterclockwise around it, a ring is walked
+    // counterclockwise outside and clockwise inside
+    fn segments(&self) -> Vec<Segment> {
+        match *self {
+            Region::Rectangle{min, max} => {
+                let corners = [min, Complex::new(max.re, min.im), max, Complex::new(min.re, max.im)];
+                (0..4).map(|i| Segment::Line(corners[i], corners[(i + 1) % 4])).collect()
+            },
+            Region::Polar{center, r, theta: None} => {
+                let mut segments = vec![Segment::Arc{center, radius: r.1, from: 0.0, to: 2.0*PI}];
+                if r.0 > 0.0 {
+                    segments.push(Segment::Arc{center, radius: r.0, from: 2.0*PI, to: 0.0});
+                }
+                segments
+            },
+            Region::Polar{center, r, theta: Some((a, b))} => {
+                let corner = |radius: f64, angle: f64| center + Complex::from_polar(radius, angle);
+                vec![
+                    Segment::Arc{center, radius: r.1, from: a, to: b},
+                    Segment::Line(corner(r.1, b), corner(r.0, b)),
+                    Segment::Arc{center, radius: r.0, from: b, to: a},
+                    Segment::Line(corner(r.0, a), corner(r.1, a)),
+                ]
+            },
+        }
+    }
+
+    // true if z is in the region, the pieces of a split share their edges, which are only counted
+    // in one of them
+    fn contains(&self, z: Complex) -> bool {
+        match *self {
+            Region::Rectangle{min, max} => Contour::Rectangle{min, max}.contains(z),
+            Region::Polar{center, r, theta} => {
+                let d = z - center;
+                let inside = r.0 <= d.abs() && d.abs() < r.1;
+                inside && theta.is_none_or(|(a, b)| (d.arg() - a).rem_euclid(2.0*PI) < b - a)
+            },
+        }
+    }
+
+    // a point in the middle of the region, where newton starts from
+    fn center(&self) -> Complex {
+        match *self {
+            Region::Rectangle{min, max} => (min + max)/2.0,
+            Region::Polar{center, r: (0.0, _), theta: None} => center,
+            Region::Polar{center, r, theta} => {
+                let (a, b) = theta.unwrap_or((0.0, 0.0));
+                center + Complex::from_polar((r.0 + r.1)/2.0, (a + b)/2.0)
+            },
+        }
+    }
+
+    // the region cut at the given fraction of its sides. A rectangle is cut in four, a disk into a
+    // smaller disk and a ring, and a ring into four sectors. The angles where a full ring is cut
+    // depend on the fraction too, so the cuts don't always go through the same nice angles
+    fn split(&self, split: f64) -> Vec<Region> {
+        match *self {
+            Region::Rectangle{min, max} => {
+                let mid = Complex::new(min.re + (max.re - min.re)*split, min.im + (max.im - min.im)*split);
+                vec![
+                    Region::Rectangle{min, max: mid},
+                    Region::Rectangle{min: Complex::new(mid.re, min.im), max: Complex::new(max.re, mid.im)},
+                    Region::Rectangle{min: Complex::new(min.re, mid.im), max: Complex::new(mid.re, max.im)},
+                    Region::Rectangle{min: mid, max},
+                ]
+            },
+            Region::Polar{center, r: (0.0, radius), theta: None} => vec![
+                Region::Polar{center, r: (0.0, radius*split), theta: None},
+                Region::Polar{center, r: (radius*split, radius), theta: None},
+            ],
+            Region::Polar{center, r, theta} => {
+                let rm = r.0 + (r.1 - r.0)*split;
+                let (a, m, b) = match theta {
+                    Some((a, b)) => (a, a + (b - a)*split, b),
+                    None => (2.0*PI*split, 2.0*PI*split + PI, 2.0*PI*(split + 1.0)),
+                };
+
+                [(r.0, rm), (rm, r.1)].into_iter()
+                    .flat_map(|r| [(a, m), (m, b)].map(|theta| Region::Polar{center, r, theta: Some(theta)}))
+                    .collect()
+            },
+        }
+    }
+}
+
+// how many pieces the boundary is first cut in, shared between its segments, each one is then
+// split further as needed
+const CONTOUR_SEGMENTS: usize = 64;
+
+// a piece of the boundary is split while f turns by more than this between its ends
+const MAX_PHASE_STEP: f64 = PI/4.0;
+
+// and it stops being split when it gets this short in t, at that point f has to be zero very close
+// to the boundary
+const MIN_SEGMENT: f64 = 1.0e-12;
+
+// counts the zeros of an analytic function inside the contour, each one as many times as its
+// multiplicity, with the argument principle: the number of zeros is (1/2*pi*i) times the integral
+// of f'/f around the contour. Since f'/f is the derivative of log(f), the integral over each piece
+// of the contour is just the change of log(f) between its ends, so we don't even need f'. The
+// pieces are split until f turns by less than pi/4 between their ends, and the change of argument
+// is taken to be that turn. That's only sampling f though, a function that winds around and back
+// between two samples (a zero very close to the contour, or very fast oscillations) can fool it,
+// so the count is as good as the sampling. It gives ZeroOnContour when there is a zero right on
+// the contour, since then the count is undefined. The function can't have poles inside the
+// contour: the argument principle really counts the zeros minus the poles, so each pole takes one
+// off the count. When that comes out negative there are surely poles inside and it gives
+// PolesInside, but otherwise the count is just wrong, with no way to tell
+pub fn count_zeros<F>(mut fx: F, contour: Contour) -> Result<usize, RootError>
+where
+    F: FnMut(Complex) -> Complex,
+{
+    region_zeros(&mut fx, Region::of(contour))
+}
+
+// count_zeros on the boundary of any region
+fn region_zeros<F>(fx: &mut F, region: Region) -> Result<usize, RootError>
+where
+    F: FnMut(Complex) -> Complex,
+{
+    let segments = region.segments();
+    let pieces = CONTOUR_SEGMENTS/segments.len();
+    let mut total = 0.0;
+
+    for segment in segments {
+        let mut eval = |t: f64| {
+            let value = fx(segment.point(t));
+            if value == Complex::ZERO {
+                Err(RootError::ZeroOnContour)
+            } else if !value.is_finite() {
+                Err(RootError::NonFinite)
+            } else {
+                Ok(value)
+            }
+        };
+
+        for k in 0..pieces {
+            let t0 = k as f64/pieces as f64;
+            let t1 = (k + 1) as f64/pieces as f64;
+            let mut pending = vec![(t0, eval(t0)?, t1, eval(t1)?)];
+
+            while let Some((t0, f0, t1, f1)) = pending.pop() {
+                let phase = (f1/f0).arg();
+
+                if phase.abs() <= MAX_PHASE_STEP {
+                    total += phase;
+                } else if t1 - t0 < MIN_SEGMENT {
+                    return Err(RootError::ZeroOnContour);
+                } else {
+                    let tm = (t0 + t1)/2.0;
+                    let fm = eval(tm)?;
+                    pending.push((tm, fm, t1, f1));
+                    pending.push((t0, f0, tm, fm));
+                }
+            }
+        }
+    }
+
+    let winding = total/(2.0*PI);
+    if winding < -0.5 {
+        return Err(RootError::PolesInside);
+    }
+
+    Ok(winding.round() as usize)
+}
+
+// the deepest contour_zeros goes when splitting the region, 2^-30 of its size is as close as two
+// distinct zeros are ever going to be told apart
+const MAX_CONTOUR_DEPTH: usize = 30;
+
+// where the regions are split, off the middle like in the sturm isolation, so the new edges don't
+// land right on nice zeros like 0 or i
+const CONTOUR_SPLITS: [f64; 2] = [0.4813, 0.5377];
+
+// finds all the zeros of an analytic function inside the contour, each one with its multiplicity.
+// They are first counted with count_zeros, then the inside of the contour is split (a rectangle in
+// smaller rectangles, a circle in sectors of rings) until every piece holds a single zero, which is
+// polished with newton_root_complex from the middle of the piece. The pieces never go outside the
+// contour, so the function only has to be free of poles inside it. A piece that still holds several
+// zeros when it can't be split anymore is a multiple zero, and is given back with that
+// multiplicity. The multiplicities always add up to the count of count_zeros
+pub fn contour_zeros<F, O>(mut fx: F, contour: Contour, options: O) -> Result<Vec<(Complex, usize)>, RootError>
+where
+    F: FnMut(Complex) -> Complex,
+    O: Into<SolverOptions>,
+{
+    let options = options.into();
+
+    let count = count_zeros(&mut fx, contour)?;
+    if count == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut zeros = Vec::new();
+    let mut pending = vec![(Region::of(contour), count, 0)];
+
+    while let Some((region, count, depth)) = pending.pop() {
+        let center = region.center();
+
+        if count == 1 {
+            if let Ok(report) = try_newton_root_complex(&mut fx, center, options) {
+                if region.contains(report.root) {
+                    zeros.push((report.root, 1));
+                    continue;
+                }
+            }
+        }
+
+        if depth >= MAX_CONTOUR_DEPTH {
+            zeros.push((center, count));
+            continue;
+        }
+
+        pending.extend(split_region(&mut fx, region, count)?.into_iter().map(|(region, count)| (region, count, depth + 1)));
+    }
+
+    zeros.sort_by(|a, b| a.0.re.total_cmp(&b.0.re).then(a.0.im.total_cmp(&b.0.im)));
+
+    Ok(zeros)
+}
+
+// splits the region and counts the zeros in each piece, dropping the empty ones. If the new edges
+// go right through a zero, or the counts don't add up, the next split point is tried
+fn split_region<F>(fx: &mut F, region: Region, count: usize) -> Result<Vec<(Region, usize)>, RootError>
+where
+    F: FnMut(Complex) -> Complex,
+{
+    let mut last_error = RootError::ZeroOnContour;
+
+    'splits: for split in CONTOUR_SPLITS {
+        let mut regions = Vec::new();
+        for piece in region.split(split) {
+            match region_zeros(&mut *fx, piece) {
+                Ok(0) => {},
+                Ok(n) => regions.push((piece, n)),
+                Err(error) => {
+                    last_error = error;
+                    continue 'splits;
+                },
+            }
+        }
+
+        if regions.iter().map(|r| r.1).sum::<usize>() == count {
+            return Ok(regions);
+        }
+    }
+
+    Err(last_error)
+}
+
+//--------------------------------------------------------------------------------------------------
+//
+// CRATE TESTS
+//
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_count_zeros() {
+        struct Test {
+            fx: fn(Complex) -> Complex,
+            contour: Contour,
+            expect: Result<usize, RootError>,
+        }
+
+        let tests = vec![
+            Test{
+                fx: |z| {z.powi(3) - 1.0},
+                contour: Contour::Circle{center: Complex::ZERO, radius: 2.0},
+                expect: Ok(3),
+            },
+            Test{
+                fx: |z| {z.powi(3) - 1.0},
+                contour: Contour::Rectangle{min: Complex::new(0.5, -1.0), max: Complex::new(2.0, 1.0)},
+                expect: Ok(1),
+            },
+            Test{
+                fx: |z| {z.sin()},
+                contour: Contour::Rectangle{min: Complex::new(-4.0, -1.0), max: Complex::new(4.0, 1.0)},
+                expect: Ok(3),
+            },
+            Test{
+                fx: |z| {(z - 1.0).powi(2)*(z + 1.0)},
+                contour: Contour::Circle{center: Complex::ZERO, radius: 2.0},
+                expect: Ok(3),
+            },
+            Test{
+                fx: |z| {z.exp()},
+                contour: Contour::Circle{center: Complex::ZERO, radius: 10.0},
+                expect: Ok(0),
+            },
+            Test{
+                fx: |z| {z},
+                contour: Contour::Circle{center: Complex::ONE, radius: 1.0},
+                expect: Err(RootError::ZeroOnContour),
+            },
+            Test{
+                // a pole and no zeros, the winding is -1
+                fx: |z| {Complex::ONE/z},
+                contour: Contour::Circle{center: Complex::ZERO, radius: 1.0},
+                expect: Err(RootError::PolesInside),
+            },
+            Test{
+                // two zeros and a pole, which only count as one
+                fx: |z| {(z*z - 0.25)/(z - 0.1)},
+                contour: Contour::Circle{center: Complex::ZERO, radius: 1.0},
+                expect: Ok(1),
+            },
+        ];
+
+        for test in tests {
+            assert_eq!(count_zeros(test.fx, test.contour), test.expect);
+        }
+    }
+
+    #[test]
+    fn test_contour_zeros() {
+        struct Test {
+            fx: fn(Complex) -> Complex,
+            contour: Contour,
+            expect: Vec<(Complex, usize)>,
+        }
+
+        let tests = vec![
+            Test{
+                fx: |z| {z.powi(3) - 1.0},
+                contour: Contour::Circle{center: Complex::ZERO, radius: 2.0},
+                expect: vec![
+                    (Complex::from_polar(1.0, -2.0*std::f64::consts::PI/3.0), 1),
+                    (Complex::from_polar(1.0, 2.0*std::f64::consts::PI/3.0), 1),
+                    (Complex::ONE, 1),
+                ],
+            },
+            Test{
+                fx: |z| {z.sin()},
+                contour: Contour::Rectangle{min: Complex::new(-4.0, -1.0), max: Complex::new(4.0, 1.0)},
+                expect: vec![
+                    (Complex::new(-std::f64::consts::PI, 0.0), 1),
+                    (Complex::ZERO, 1),
+                    (Complex::new(std::f64::consts::PI, 0.0), 1),
+                ],
+            },
+            Test{
+                // the zeros at 1 +- 1.5i are in the bounding square of the circle but not inside
+                fx: |z| {(z - 0.5).powi(2)*((z - 1.0)*(z - 1.0) + 2.25)},
+                contour: Contour::Circle{center: Complex::ZERO, radius: 1.4},
+                expect: vec![(Complex::new(0.5, 0.0), 2)],
+            },
+            Test{
+                // a pole outside the circle, but inside its bounding square
+                fx: |z| {(z - 0.5)/(z - Complex::new(1.2, 1.2))},
+                contour: Contour::Circle{center: Complex::ZERO, radius: 1.4},
+                expect: vec![(Complex::new(0.5, 0.0), 1)],
+            },
+            Test{
+                // a zero outside the circle, close to it
+                fx: |z| {(z - 0.5)*(z - Complex::new(1.4, 1.0))},
+                contour: Contour::Circle{center: Complex::ZERO, radius: 1.4},
+                expect: vec![(Complex::new(0.5, 0.0), 1)],
+            },
+            Test{
+                // zeros near the center and all around, in several of the ring sectors
+                fx: |z| {z*(z.powi(5) - 0.5)},
+                contour: Contour::Circle{center: Complex::new(0.05, -0.05), radius: 1.0},
+                expect: {
+                    let mut zeros: Vec<_> = (0..5).map(|k| (Complex::from_polar(0.5_f64.powf(0.2), 2.0*std::f64::consts::PI*k as f64/5.0), 1)).collect();
+                    zeros.push((Complex::ZERO, 1));
+                    zeros.sort_by(|a: &(Complex, usize), b| a.0.re.total_cmp(&b.0.re).then(a.0.im.total_cmp(&b.0.im)));
+                    zeros
+                },
+            },
+            Test{
+                // the poles of a transfer function (s + 1)/(s^2 + 0.4s + 4), as the zeros of its
+                // inverse. The zero of the transfer function at -1 is a pole of the inverse, so it
+                // has to stay out of the rectangle
+                fx: |z| {(z*z + 0.4*z + 4.0)/(z + 1.0)},
+                contour: Contour::Rectangle{min: Complex::new(-0.5, -3.0), max: Complex::new(0.5, 3.0)},
+                expect: vec![
+                    (Complex::new(-0.2, -(4.0_f64 - 0.04).sqrt()), 1),
+                    (Complex::new(-0.2, (4.0_f64 - 0.04).sqrt()), 1),
+                ],
+            },
+        ];
+
+        for test in tests {
+            let zeros = contour_zeros(test.fx, test.contour, None).unwrap();
+            assert_eq!(zeros.len(), test.expect.len(), "{:?}", zeros);
+
+            for ((zero, multiplicity), (expect, expect_multiplicity)) in zeros.into_iter().zip(test.expect) {
+                assert!((zero - expect).abs() < 1.0e-7, "{} {}", zero, expect);
+                assert_eq!(multiplicity, expect_multiplicity);
+            }
+        }
+    }
+}
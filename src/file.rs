@@ -11,6 +11,7 @@ impl DataFile {
         let file = File::options()
             .write(true)
             .append(false)
+            .truncate(true)
             .create(true)
             .open(path).unwrap();
 
@@ -22,4 +23,15 @@ impl DataFile {
         let row = format!(" {} {}\n", first_column, second_column);
         self.f.write_all(row.as_bytes()).unwrap();
     }
+
+    // writes a row with any number of columns, in the same format as write
+    pub fn write_row(&mut self, columns: &[f64]) {
+        let mut row = String::new();
+        for column in columns {
+            row.push_str(&format!(" {}", column));
+        }
+        row.push('\n');
+
+        self.f.write_all(row.as_bytes()).unwrap();
+    }
 }
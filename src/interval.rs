@@ -0,0 +1,475 @@
+use std::f64::consts::{FRAC_PI_2, PI};
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+// a closed interval [lo, hi] of real numbers, with arithmetic that always rounds outwards, so the
+// result of any computation holds every value the same computation could give on any numbers of the
+// operands. The basic operations and sqrt are correctly rounded by IEEE, so moving the bounds one
+// float out is enough, the other elementary functions come from libm, which is only good to about
+// an ulp, and are moved out a few more. Keep in mind that an f64 constant (like 0.1) is turned into
+// the point interval of that float, not of the real number it was written from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interval {
+    pub lo: f64,
+    pub hi: f64,
+}
+
+// how many floats the results of the libm functions are moved out
+const LIBM_ULPS: usize = 2;
+
+impl Interval {
+    pub const ENTIRE: Interval = Interval{lo: f64::NEG_INFINITY, hi: f64::INFINITY};
+
+    // the interval holding no numbers at all, which is what sqrt and ln give on intervals entirely
+    // outside their domain. Its bounds are NaN, the arithmetic and the elementary functions on it
+    // give it back, intersect gives None and hull just the other interval
+    pub const EMPTY: Interval = Interval{lo: f64::NAN, hi: f64::NAN};
+
+    // the interval between lo and hi, in any order
+    pub fn new(lo: f64, hi: f64) -> Interval {
+        Interval{lo: lo.min(hi), hi: hi.max(lo)}
+    }
+
+    // the interval holding just x
+    pub fn point(x: f64) -> Interval {
+        Interval{lo: x, hi: x}
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.lo.is_nan() || self.hi.is_nan()
+    }
+
+    pub fn width(self) -> f64 {
+        self.hi - self.lo
+    }
+
+    // the middle of the interval, always inside it, even for huge bounds. NaN for the empty one. An
+    // unbounded interval has no middle, so it gives a finite point a bit away from the finite end
+    // (or 0 for the entire line)
+    pub fn mid(self) -> f64 {
+        if self.is_empty() {
+            return f64::NAN;
+        } else if self.lo == f64::NEG_INFINITY && self.hi == f64::INFINITY {
+            return 0.0;
+        } else if self.hi == f64::INFINITY {
+            return (self.lo + self.lo.abs().max(1.0)).min(f64::MAX).max(self.lo);
+        } else if self.lo == f64::NEG_INFINITY {
+            return (self.hi - self.hi.abs().max(1.0)).max(f64::MIN).min(self.hi);
+        }
+
+        (self.lo/2.0 + self.hi/2.0).clamp(self.lo, self.hi)
+    }
+
+    pub fn contains(self, x: f64) -> bool {
+        self.lo <= x && x <= self.hi
+    }
+
+    // true if the interval is inside the interior of the other one, which is what the interval
+    // newton theorem needs to prove a root exists
+    pub fn is_interior_of(self, other: Interval) -> bool {
+        other.lo < self.lo && self.hi < other.hi
+    }
+
+    // the numbers in both intervals, None if there are none
+    pub fn intersect(self, other: Interval) -> Option<Interval> {
+        if self.is_empty() || other.is_empty() {
+            return None;
+        }
+
+        let lo = self.lo.max(other.lo);
+        let hi = self.hi.min(other.hi);
+
+        if lo <= hi { Some(Interval{lo, hi}) } else { None }
+    }
+
+    // the smallest interval holding both
+    pub fn hull(self, other: Interval) -> Interval {
+        Interval{lo: self.lo.min(other.lo), hi: self.hi.max(other.hi)}
+    }
+
+    // the interval split in two at its middle
+    pub fn bisect(self) -> (Interval, Interval) {
+        let mid = self.mid();
+        (Interval{lo: self.lo, hi: mid}, Interval{lo: mid, hi: self.hi})
+    }
+
+    // the square root of the non negative part of the interval, EMPTY if it has none
+    pub fn sqrt(self) -> Interval {
+        if self.is_empty() || self.hi < 0.0 {
+            return Interval::EMPTY;
+        }
+
+        Interval{lo: self.lo.max(0.0).sqrt().next_down().max(0.0), hi: self.hi.sqrt().next_up()}
+    }
+
+    pub fn exp(self) -> Interval {
+        if self.is_empty() {
+            return Interval::EMPTY;
+        }
+
+        Interval{lo: down(self.lo.exp(), LIBM_ULPS).max(0.0), hi: up(self.hi.exp(), LIBM_ULPS)}
+    }
+
+    // the logarithm of the positive part of the interval, EMPTY if it has none. It goes down to -inf
+    // when the interval reaches 0
+    pub fn ln(self) -> Interval {
+        if self.is_empty() || self.hi <= 0.0 {
+            return Interval::EMPTY;
+        }
+
+        Interval{lo: down(self.lo.max(0.0).ln(), LIBM_ULPS), hi: up(self.hi.ln(), LIBM_ULPS)}
+    }
+
+    pub fn sin(self) -> Interval {
+        periodic(self, f64::sin, 0.0)
+    }
+
+    pub fn cos(self) -> Interval {
+        periodic(self, f64::cos, FRAC_PI_2)
+    }
+
+    // x^n, the even powers of an interval around zero start at zero instead of going negative like
+    // multiplying the interval by itself would
+    pub fn powi(self, n: i32) -> Interval {
+        if self.is_empty() {
+            return Interval::EMPTY;
+        } else if n == 0 {
+            return Interval::point(1.0);
+        } else if n < 0 {
+            // -i32::MIN doesn't fit an i32, so the power goes through an u32
+            return Interval::point(1.0)/self.powu(n.unsigned_abs());
+        }
+
+        self.powu(n as u32)
+    }
+
+    // x^n for n > 0, f64::powi is good to about n ulps
+    fn powu(self, n: u32) -> Interval {
+        let Ok(exponent) = i32::try_from(n) else {
+            return self.powu(n/2).powu(2);
+        };
+
+        let ulps = n as usize;
+        let (lo, hi) = (self.lo.powi(exponent), self.hi.powi(exponent));
+
+        if n % 2 == 1 {
+            Interval{lo: down(lo, ulps), hi: up(hi, ulps)}
+        } else if self.lo >= 0.0 {
+            Interval{lo: down(lo, ulps).max(0.0), hi: up(hi, ulps)}
+        } else if self.hi <= 0.0 {
+            Interval{lo: down(hi, ulps).max(0.0), hi: up(lo, ulps)}
+        } else {
+            Interval{lo: 0.0, hi: up(lo.max(hi), ulps)}
+        }
+    }
+}
+
+// up to this many floats down or up they are counted one by one, further than that the bound is
+// moved n*eps relative to it (plus n of the smallest floats), which is always at least as far
+const COUNTED_ULPS: usize = 64;
+
+// x moved n floats down or up
+fn down(mut x: f64, n: usize) -> f64 {
+    if n > COUNTED_ULPS && x.is_finite() {
+        return (x - n as f64*(x.abs()*f64::EPSILON + f64::from_bits(1))).next_down();
+    }
+
+    for _ in 0..n.min(COUNTED_ULPS) {
+        x = x.next_down();
+    }
+
+    x
+}
+
+fn up(mut x: f64, n: usize) -> f64 {
+    if n > COUNTED_ULPS && x.is_finite() {
+        return (x + n as f64*(x.abs()*f64::EPSILON + f64::from_bits(1))).next_up();
+    }
+
+    for _ in 0..n.min(COUNTED_ULPS) {
+        x = x.next_up();
+    }
+
+    x
+}
+
+// sin or cos over an interval, fx(x + shift) is sin, so fx has its maximums at pi/2 - shift + 2k*pi
+// and its minimums at -pi/2 - shift + 2k*pi. Those are found with the float pi, which is a bit off,
+// so any extreme close enough to the interval counts as inside it, which only makes the result wider
+fn periodic(x: Interval, fx: fn(f64) -> f64, shift: f64) -> Interval {
+    if x.is_empty() {
+        return Interval::EMPTY;
+    } else if x.width().is_nan() || x.width() >= 2.0*PI {
+        return Interval{lo: -1.0, hi: 1.0};
+    }
+
+    let (a, b) = (fx(x.lo), fx(x.hi));
+    let mut lo = down(a.min(b), LIBM_ULPS);
+    let mut hi = up(a.max(b), LIBM_ULPS);
+
+    let slack = 1.0e-12*(1.0 + x.lo.abs().max(x.hi.abs()));
+    let first = ((x.lo - slack + shift - FRAC_PI_2)/PI).floor() as i64;
+    let last = ((x.hi + slack + shift - FRAC_PI_2)/PI).ceil() as i64;
+
+    for k in first..=last {
+        let extreme = k as f64*PI + FRAC_PI_2 - shift;
+        if x.lo - slack <= extreme && extreme <= x.hi + slack {
+            if k.rem_euclid(2) == 0 { hi = 1.0 } else { lo = -1.0 }
+        }
+    }
+
+    Interval{lo: lo.max(-1.0), hi: hi.min(1.0)}
+}
+
+// a product of two bounds, where 0*inf is 0 since the infinite bound is never actually reached
+fn product(a: f64, b: f64) -> f64 {
+    if a == 0.0 || b == 0.0 { 0.0 } else { a*b }
+}
+
+impl From<f64> for Interval {
+    fn from(x: f64) -> Interval {
+        Interval::point(x)
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}, {}]", self.lo, self.hi)
+    }
+}
+
+impl Neg for Interval {
+    type Output = Interval;
+
+    fn neg(self) -> Interval {
+        Interval{lo: -self.hi, hi: -self.lo}
+    }
+}
+
+impl Add for Interval {
+    type Output = Interval;
+
+    fn add(self, other: Interval) -> Interval {
+        Interval{lo: (self.lo + other.lo).next_down(), hi: (self.hi + other.hi).next_up()}
+    }
+}
+
+impl Sub for Interval {
+    type Output = Interval;
+
+    fn sub(self, other: Interval) -> Interval {
+        Interval{lo: (self.lo - other.hi).next_down(), hi: (self.hi - other.lo).next_up()}
+    }
+}
+
+impl Mul for Interval {
+    type Output = Interval;
+
+    fn mul(self, other: Interval) -> Interval {
+        if self.is_empty() || other.is_empty() {
+            return Interval::EMPTY;
+        }
+
+        let products = [
+            product(self.lo, other.lo),
+            product(self.lo, other.hi),
+            product(self.hi, other.lo),
+            product(self.hi, other.hi),
+        ];
+
+        let lo = products.iter().copied().fold(f64::INFINITY, f64::min);
+        let hi = products.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+
+        Interval{lo: lo.next_down(), hi: hi.next_up()}
+    }
+}
+
+// dividing by an interval holding zero can give anything
+impl Div for Interval {
+    type Output = Interval;
+
+    fn div(self, other: Interval) -> Interval {
+        if self.is_empty() || other.is_empty() {
+            return Interval::EMPTY;
+        } else if other.contains(0.0) {
+            return Interval::ENTIRE;
+        }
+
+        let inverse = Interval{lo: (1.0/other.hi).next_down(), hi: (1.0/other.lo).next_up()};
+        self*inverse
+    }
+}
+
+// the mixed operations with f64 on both sides, and the assign versions of everything
+macro_rules! impl_interval_ops {
+    ($($trait:ident $method:ident $assign_trait:ident $assign_method:ident),*) => {$(
+        impl $trait<f64> for Interval {
+            type Output = Interval;
+
+            fn $method(self, other: f64) -> Interval {
+                $trait::$method(self, Interval::from(other))
+            }
+        }
+
+        impl $trait<Interval> for f64 {
+            type Output = Interval;
+
+            fn $method(self, other: Interval) -> Interval {
+                $trait::$method(Interval::from(self), other)
+            }
+        }
+
+        impl $assign_trait for Interval {
+            fn $assign_method(&mut self, other: Interval) {
+                *self = $trait::$method(*self, other);
+            }
+        }
+
+        impl $assign_trait<f64> for Interval {
+            fn $assign_method(&mut self, other: f64) {
+                *self = $trait::$method(*self, other);
+            }
+        }
+    )*};
+}
+
+impl_interval_ops!(
+    Add add AddAssign add_assign,
+    Sub sub SubAssign sub_assign,
+    Mul mul MulAssign mul_assign,
+    Div div DivAssign div_assign
+);
+
+//--------------------------------------------------------------------------------------------------
+//
+// CRATE TESTS
+//
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_interval_arithmetic() {
+        struct Test {
+            result: Interval,
+            // what the result has to hold, and what it can't be much wider than
+            expect: Interval,
+        }
+
+        let a = Interval::new(1.0, 2.0);
+        let b = Interval::new(-3.0, 4.0);
+        let pi = std::f64::consts::PI;
+
+        let tests = vec![
+            Test{
+                result: a + b,
+                expect: Interval::new(-2.0, 6.0),
+            },
+            Test{
+                result: a - b,
+                expect: Interval::new(-3.0, 5.0),
+            },
+            Test{
+                result: a*b,
+                expect: Interval::new(-6.0, 8.0),
+            },
+            Test{
+                result: b/a,
+                expect: Interval::new(-3.0, 4.0),
+            },
+            Test{
+                result: 2.0*a - 1.0,
+                expect: Interval::new(1.0, 3.0),
+            },
+            Test{
+                result: b.powi(2),
+                expect: Interval::new(0.0, 16.0),
+            },
+            Test{
+                result: b.powi(3),
+                expect: Interval::new(-27.0, 64.0),
+            },
+            Test{
+                result: Interval::new(-2.0, -1.0).powi(2),
+                expect: Interval::new(1.0, 4.0),
+            },
+            Test{
+                result: a.sqrt(),
+                expect: Interval::new(1.0, 2.0_f64.sqrt()),
+            },
+            Test{
+                result: a.exp().ln(),
+                expect: a,
+            },
+            Test{
+                result: Interval::new(0.0, 3.0).sin(),
+                expect: Interval::new(0.0, 1.0),
+            },
+            Test{
+                result: Interval::new(-1.0, 1.0).cos(),
+                expect: Interval::new(1.0_f64.cos(), 1.0),
+            },
+            Test{
+                result: Interval::new(2.0, 4.0).cos(),
+                expect: Interval::new(-1.0, 2.0_f64.cos()),
+            },
+            Test{
+                result: Interval::new(0.0, 10.0).sin(),
+                expect: Interval::new(-1.0, 1.0),
+            },
+        ];
+
+        for test in tests {
+            assert!(test.result.lo <= test.expect.lo && test.expect.hi <= test.result.hi, "{} {}", test.result, test.expect);
+            assert!(test.result.width() - test.expect.width() < 1.0e-14*(1.0 + test.expect.width()), "{} {}", test.result, test.expect);
+        }
+
+        // rounding outwards, the real sum of the floats 0.1 and 0.2 isn't a float, and it is held
+        let sum = Interval::point(0.1) + Interval::point(0.2);
+        assert!(sum.lo < sum.hi && sum.contains(0.1 + 0.2));
+
+        // sin(pi) is about 1.2e-16, the sin of the interval of the float pi has to hold it
+        assert!(Interval::point(pi).sin().contains(pi.sin()));
+
+        assert_eq!(a/b, Interval::ENTIRE);
+        assert_eq!(Interval::ENTIRE*Interval::point(0.0), Interval::new(0.0_f64.next_down(), 0.0_f64.next_up()));
+        assert_eq!(a.intersect(Interval::new(3.0, 4.0)), None);
+        assert_eq!(a.intersect(b), Some(a));
+        assert_eq!(a.hull(b), b);
+        assert!(a.is_interior_of(Interval::new(0.0, 3.0)) && !a.is_interior_of(Interval::new(1.0, 3.0)));
+        assert_eq!(a.bisect(), (Interval::new(1.0, 1.5), Interval::new(1.5, 2.0)));
+        assert_eq!(Interval::new(3.0, f64::INFINITY).bisect(), (Interval::new(3.0, 6.0), Interval::new(6.0, f64::INFINITY)));
+        assert_eq!(Interval::new(f64::NEG_INFINITY, 0.0).mid(), -1.0);
+        assert_eq!(Interval::ENTIRE.mid(), 0.0);
+        assert_eq!(format!("{}", a), "[1, 2]");
+
+        // the negative powers go through the reciprocal, even the one whose opposite isn't an i32,
+        // and the huge ones are still rounded outwards
+        let cube = Interval::new(0.5, 4.0).powi(-3);
+        assert!(cube.contains(1.0/64.0) && cube.contains(8.0) && cube.width() < 8.0);
+        assert!(Interval::point(1.0).powi(i32::MIN).contains(1.0) && Interval::point(1.0).powi(i32::MAX).contains(1.0));
+        let tiny = Interval::new(-3.0, -2.0).powi(i32::MIN);
+        assert!(tiny.contains(0.0) && tiny.hi < 1.0e-300);
+        assert_eq!(Interval::new(0.5, 0.9).powi(i32::MIN).hi, f64::INFINITY);
+
+        // sqrt and ln only look at the part of the interval in their domain, and there may be none
+        let ln = Interval::new(-1.0, 2.0).ln();
+        assert!(ln.lo == f64::NEG_INFINITY && ln.contains(2.0_f64.ln()) && ln.hi < 0.7);
+        assert_eq!(Interval::new(-1.0, 4.0).sqrt().lo, 0.0);
+        assert!(Interval::new(-4.0, -1.0).sqrt().is_empty());
+        assert!(Interval::new(-4.0, 0.0).ln().is_empty());
+        assert_eq!(Interval::new(-4.0, 0.0).sqrt(), Interval::new(0.0, 0.0_f64.next_up()));
+
+        // and the empty interval stays empty through everything
+        let empty = Interval::EMPTY;
+        for result in [empty + a, a - empty, empty*a, empty/b, b/empty, empty.powi(2), empty.exp(), empty.sin(), empty.sqrt()] {
+            assert!(result.is_empty(), "{}", result);
+        }
+        assert!(!a.is_empty() && !Interval::ENTIRE.is_empty());
+        assert_eq!(empty.intersect(a), None);
+        assert_eq!(empty.hull(a), a);
+        assert!(!empty.contains(0.0) && empty.mid().is_nan());
+    }
+}
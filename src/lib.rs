@@ -1,21 +1,307 @@
+mod complex;
+mod continuation;
+mod contour;
 mod file;
+mod interval;
+mod polynomial;
 mod root;
+pub use complex::Complex;
+pub use continuation::*;
+pub use contour::*;
 pub use file::DataFile;
+pub use interval::Interval;
+pub use polynomial::Polynomial;
 pub use root::*;
 
 // calculates the derivative of a certain function at a certain point, with a
 // number of steps that defaults to 20, this doesn't affect much in terms of performance,
 // but there is no need to have like 100 steps
-pub fn calculate_derivative(fx: fn(f64) -> f64, x: f64) -> f64 {
+pub fn calculate_derivative<F>(mut fx: F, x: f64) -> f64
+where
+    F: FnMut(f64) -> f64,
+{
     let eps = 1.0/(2.0_f64).powf(20.0); // I think this is a magic number for me hehe
 
     (fx(x + eps) - fx(x - eps)) / (2.0 * eps)
 }
 
+// the ratio the step is shrunk by on each round of calculate_derivative_with_error, and how many
+// rounds it takes at most
+const RIDDERS_SHRINK: f64 = 1.4;
+const RIDDERS_ROUNDS: usize = 10;
+
+// calculates the derivative of a certain function at a certain point with ridders' method, and
+// gives back an estimate of its error too. It starts from the central difference with a step of h
+// (by default a tenth of max(|x|, 1), so it scales with x but doesn't vanish near 0), then keeps
+// shrinking the step and richardson extrapolating the differences to step zero, stopping when the
+// error stops getting smaller. The error is estimated from how much the last extrapolations
+// differ, so take it as an order of magnitude. It is usually good to 1e-13 or so, way better than
+// calculate_derivative, at the cost of up to 20 calls to fx. The function has to be defined on
+// [x - h, x + h], so near the edges of its domain (like ln close to 0) pass a smaller h, a tenth
+// of the distance to the edge does
+pub fn calculate_derivative_with_error<F>(mut fx: F, x: f64, h: Option<f64>) -> (f64, f64)
+where
+    F: FnMut(f64) -> f64,
+{
+    let mut h = h.unwrap_or(0.1*x.abs().max(1.0));
+    let shrink2 = RIDDERS_SHRINK*RIDDERS_SHRINK;
+
+    let mut previous = vec![(fx(x + h) - fx(x - h))/(2.0*h)];
+    let mut derivative = previous[0];
+    let mut error = f64::INFINITY;
+
+    for _ in 1..RIDDERS_ROUNDS {
+        h /= RIDDERS_SHRINK;
+
+        // each column extrapolates the one before it to a higher order in h
+        let mut current = vec![(fx(x + h) - fx(x - h))/(2.0*h)];
+        let mut factor = shrink2;
+
+        for j in 1..=previous.len() {
+            let extrapolated = (current[j - 1]*factor - previous[j - 1])/(factor - 1.0);
+            factor *= shrink2;
+
+            let estimate = (extrapolated - current[j - 1]).abs().max((extrapolated - previous[j - 1]).abs());
+            if estimate <= error {
+                error = estimate;
+                derivative = extrapolated;
+            }
+            current.push(extrapolated);
+        }
+
+        // the highest order got a lot worse, rounding took over and there is nothing left to gain
+        let last = current.len() - 1;
+        let diverging = (current[last] - previous[last - 1]).abs() >= 2.0*error;
+        previous = current;
+
+        if diverging {
+            break;
+        }
+    }
+
+    (derivative, error)
+}
+
+// the points a finite difference stencil takes around x. Central takes them on both sides, Forward
+// only at x and after it and Backward only at x and before it, for functions that aren't defined
+// on the other side (like sqrt at 0). The one sided ones take n + p points for order p, against
+// 2*((n + 1)/2) - 1 + p for central, so at most one more, but their error constant is larger, so
+// for the same step they are less accurate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Stencil {
+    #[default]
+    Central,
+    Forward,
+    Backward,
+}
+
+impl Stencil {
+    // the order the stencil is really accurate to when asked for the given one, central stencils
+    // only come in even orders, so odd ones are rounded up
+    fn order(self, order: usize) -> usize {
+        match self {
+            Stencil::Central => order.max(1).div_ceil(2)*2,
+            Stencil::Forward | Stencil::Backward => order.max(1),
+        }
+    }
+
+    // the offsets of the stencil for the nth derivative accurate to the given order, in units of
+    // the step
+    fn offsets(self, n: usize, order: usize) -> Vec<f64> {
+        let order = self.order(order);
+
+        match self {
+            Stencil::Central => {
+                let half = (n.div_ceil(2) - 1 + order/2) as i64;
+                (-half..=half).map(|k| k as f64).collect()
+            },
+            Stencil::Forward => (0..n + order).map(|k| k as f64).collect(),
+            Stencil::Backward => (0..n + order).map(|k| -(k as f64)).collect(),
+        }
+    }
+}
+
+// calculates the nth derivative of a certain function at a certain point with a central difference
+// stencil, accurate to the given order in the step (which defaults to 4, and is rounded up to an
+// even number, since that's all central stencils give). The stencil for the nth derivative with
+// order p takes 2*((n + 1)/2) - 1 + p points, spaced by a step that balances the truncation error
+// against the rounding, eps^(1/(n + p)) relative to x, with the weights from
+// finite_difference_weights. For n = 0 it just gives fx(x)
+pub fn nth_derivative<F>(fx: F, x: f64, n: usize, order: Option<usize>) -> f64
+where
+    F: FnMut(f64) -> f64,
+{
+    nth_derivative_with_stencil(fx, x, n, order, Stencil::Central)
+}
+
+// same as nth_derivative, but with the given stencil, the one sided ones take n + p points
+pub fn nth_derivative_with_stencil<F>(mut fx: F, x: f64, n: usize, order: Option<usize>, stencil: Stencil) -> f64
+where
+    F: FnMut(f64) -> f64,
+{
+    if n == 0 {
+        return fx(x);
+    }
+
+    let order = stencil.order(order.unwrap_or(4));
+    let offsets = stencil.offsets(n, order);
+
+    apply_stencil(&mut fx, x, n, &offsets, derivative_step(x.abs().max(1.0), n, order))
+}
+
+// same as nth_derivative, but for a function only defined on the closed interval domain (either
+// end can be infinite). The central stencil is used when it fits in the domain, and otherwise the
+// one sided one towards the side with more room, with the step shrunk if even that one doesn't fit
+// the room on that side. Below |x| = 1 the step is relative to |x| instead of 1 (but never to less
+// than the distance to the closest end), which is what functions blowing up at 0, like ln or 1/x,
+// need close to it. A function blowing up at an end away from 0 isn't told apart from one that is
+// fine there, so shift it to put that end at 0. x itself has to be in the domain, and the domain
+// can't be a single point, it gives NaN otherwise
+pub fn nth_derivative_in_domain<F>(mut fx: F, x: f64, n: usize, order: Option<usize>, domain: (f64, f64)) -> f64
+where
+    F: FnMut(f64) -> f64,
+{
+    let (lo, hi) = domain;
+    if !(lo <= x && x <= hi) || lo == hi {
+        return f64::NAN;
+    } else if n == 0 {
+        return fx(x);
+    }
+
+    // right at an end at 0 the function has to be fine there, so it is the usual scale of 1
+    let distance = (x - lo).min(hi - x);
+    let scale = match x.abs().max(distance).min(x.abs().max(1.0)) {
+        0.0 => 1.0,
+        scale => scale,
+    };
+
+    let order = order.unwrap_or(4);
+    let central = Stencil::Central.offsets(n, order);
+    let h = derivative_step(scale, n, Stencil::Central.order(order));
+    let reach = central[central.len() - 1]*h;
+
+    if lo <= x - reach && x + reach <= hi {
+        return apply_stencil(&mut fx, x, n, &central, h);
+    }
+
+    let (stencil, room) = if hi - x >= x - lo { (Stencil::Forward, hi - x) } else { (Stencil::Backward, x - lo) };
+    let offsets = stencil.offsets(n, order);
+    let h = derivative_step(scale, n, stencil.order(order)).min(room/(offsets.len() - 1) as f64);
+
+    apply_stencil(&mut fx, x, n, &offsets, h)
+}
+
+// the step for the nth derivative with a stencil accurate to the given order at a point of the
+// given scale (max(|x|, 1) unless something closer matters), it balances the truncation error
+// against the rounding
+fn derivative_step(scale: f64, n: usize, order: usize) -> f64 {
+    f64::EPSILON.powf(1.0/(n + order) as f64)*scale
+}
+
+// the sum of the weights times fx at the points of the stencil, over h^n
+fn apply_stencil<F>(fx: &mut F, x: f64, n: usize, offsets: &[f64], h: f64) -> f64
+where
+    F: FnMut(f64) -> f64,
+{
+    let weights = finite_difference_weights(offsets, n);
+
+    let sum: f64 = offsets.iter().zip(&weights).map(|(k, w)| w*fx(x + k*h)).sum();
+    sum/h.powi(n as i32)
+}
+
+// calculates the weights of the finite difference stencil for the nth derivative at 0 on the given
+// offsets, which can be any distinct points, in any order and not evenly spaced, with fornberg's
+// algorithm. So the nth derivative of f at x is about the sum of weights[i]*f(x + offsets[i]*h),
+// divided by h^n when the offsets are given in units of a step h. With m offsets the stencil is
+// exact for polynomials up to degree m - 1, so it needs more than n of them (the weights are all
+// zero otherwise). The weights for all the lower derivatives come out along the way, this only
+// keeps the ones asked for
+pub fn finite_difference_weights(offsets: &[f64], n: usize) -> Vec<f64> {
+    let m = offsets.len();
+    if m == 0 {
+        return Vec::new();
+    }
+
+    let mut weights = vec![vec![0.0; n + 1]; m];
+    weights[0][0] = 1.0;
+
+    let mut c1 = 1.0;
+    let mut c4 = offsets[0];
+
+    for i in 1..m {
+        let orders = i.min(n);
+        let mut c2 = 1.0;
+        let c5 = c4;
+        c4 = offsets[i];
+
+        for j in 0..i {
+            let c3 = offsets[i] - offsets[j];
+            c2 *= c3;
+
+            // the weights of the new point, from the ones of the point before it
+            if j == i - 1 {
+                for k in (1..=orders).rev() {
+                    weights[i][k] = c1*(k as f64*weights[i - 1][k - 1] - c5*weights[i - 1][k])/c2;
+                }
+                weights[i][0] = -c1*c5*weights[i - 1][0]/c2;
+            }
+
+            // and the update of the old ones
+            for k in (1..=orders).rev() {
+                weights[j][k] = (c4*weights[j][k] - k as f64*weights[j][k - 1])/c3;
+            }
+            weights[j][0] = c4*weights[j][0]/c3;
+        }
+
+        c1 = c2;
+    }
+
+    weights.into_iter().map(|w| w[n]).collect()
+}
+
+// the same as calculate_derivative for a complex function, the step is taken along the real axis,
+// which gives the complex derivative as long as the function is analytic
+pub fn calculate_complex_derivative<F>(mut fx: F, z: Complex) -> Complex
+where
+    F: FnMut(Complex) -> Complex,
+{
+    let eps = 1.0/(2.0_f64).powf(20.0);
+
+    (fx(z + eps) - fx(z - eps)) / (2.0 * eps)
+}
+
+// calculates the jacobian of a function from R^n to R^m at a certain point, the same way
+// calculate_derivative does, with a central difference on each component of x. Row i holds the
+// derivatives of the ith component of fx, so it costs 2n calls to fx
+pub fn calculate_jacobian<F>(mut fx: F, x: &[f64]) -> Vec<Vec<f64>>
+where
+    F: FnMut(&[f64]) -> Vec<f64>,
+{
+    let eps = 1.0/(2.0_f64).powf(20.0);
+    let mut xtemp = x.to_vec();
+    let mut columns = Vec::with_capacity(x.len());
+
+    for j in 0..x.len() {
+        xtemp[j] = x[j] + eps;
+        let forward = fx(&xtemp);
+        xtemp[j] = x[j] - eps;
+        let backward = fx(&xtemp);
+        xtemp[j] = x[j];
+
+        columns.push(forward.iter().zip(&backward).map(|(f, b)| (f - b)/(2.0*eps)).collect::<Vec<f64>>());
+    }
+
+    let rows = columns.first().map_or(0, |c| c.len());
+    (0..rows).map(|i| columns.iter().map(|c| c[i]).collect()).collect()
+}
+
 // calculates the riemann sum of a certain function at a certain point with an optinal number of
 // rectangles which defaults to 100000, this is a very slow function, but it is a good way to
 // calculate the area of a function
-pub fn calculate_integral_with_rectangles(fx: fn(f64) -> f64, xmin: f64, xmax: f64, n_rectangles: Option<i64>) -> f64 {
+pub fn calculate_integral_with_rectangles<F>(mut fx: F, xmin: f64, xmax: f64, n_rectangles: Option<i64>) -> f64
+where
+    F: FnMut(f64) -> f64,
+{
     let n_rectangles = n_rectangles.unwrap_or(100000);
 
     let delx = (xmax - xmin)/n_rectangles as f64;
@@ -81,6 +367,347 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_calculate_derivative_with_error() {
+        struct Test {
+            fx: fn(f64) -> f64,
+            x: f64,
+            expect: f64,
+        }
+
+        let tests = vec![
+            Test{
+                fx: |x| {x.sin()},
+                x: 1.0,
+                expect: 1.0_f64.cos(),
+            },
+            Test{
+                fx: |x| {x.exp()},
+                x: 50.0,
+                expect: 50.0_f64.exp(),
+            },
+            Test{
+                // with the fixed 2^-20 step this is way off at this scale
+                fx: |x| {x.powf(3.0)},
+                x: 1.0e6,
+                expect: 3.0e12,
+            },
+            Test{
+                fx: |x| {(x*1.0e-8).sin()},
+                x: 0.0,
+                expect: 1.0e-8,
+            },
+            Test{
+                fx: |x| {1.0/(1.0 + x*x)},
+                x: 2.0,
+                expect: -4.0/25.0,
+            },
+        ];
+
+        for test in tests {
+            let (derivative, error) = calculate_derivative_with_error(test.fx, test.x, None);
+            let scale = test.expect.abs();
+
+            assert!((derivative - test.expect).abs() < 1.0e-13*scale, "{} {}", derivative, test.expect);
+            assert!(error < 1.0e-13*scale, "{} {}", error, scale);
+        }
+
+        // the default step doesn't shrink with x, a tiny x is no different from 0
+        struct Tiny {
+            fx: fn(f64) -> f64,
+            dfx: fn(f64) -> f64,
+        }
+
+        let tiny = vec![
+            Tiny{fx: |x| {x + 1.0}, dfx: |_| {1.0}},
+            Tiny{fx: |x| {x.cos()}, dfx: |x| {-x.sin()}},
+            Tiny{fx: |x| {x.exp()}, dfx: |x| {x.exp()}},
+        ];
+
+        for test in tiny {
+            for x in [1.0e-20, 1.0e-12, 1.0e-8, -1.0e-8] {
+                let (derivative, error) = calculate_derivative_with_error(test.fx, x, None);
+                assert!((derivative - (test.dfx)(x)).abs() < 1.0e-13, "{} {} {}", x, derivative, (test.dfx)(x));
+                assert!(error < 1.0e-13 && (derivative - (test.dfx)(x)).abs() <= 10.0*error.max(f64::EPSILON), "{} {}", x, error);
+            }
+        }
+
+        // near the edge of the domain the step has to be given, a tenth of the distance to it does
+        for x in [0.5, 0.1, 1.0e-2, 1.0e-3, 1.0e-6, 1.0e-9] {
+            let (derivative, error) = calculate_derivative_with_error(|x| x.ln(), x, Some(0.1*x));
+            assert!((derivative*x - 1.0).abs() < 1.0e-12, "{} {}", x, derivative);
+            assert!(error < 1.0e-12/x, "{} {}", x, error);
+        }
+    }
+
+    #[test]
+    fn test_nth_derivative() {
+        struct Test {
+            fx: fn(f64) -> f64,
+            x: f64,
+            n: usize,
+            order: Option<usize>,
+            expect: f64,
+            precision: f64,
+        }
+
+        let tests = vec![
+            Test{
+                fx: |x| {x.sin()},
+                x: 1.0,
+                n: 1,
+                order: None,
+                expect: 1.0_f64.cos(),
+                precision: 1.0e-12,
+            },
+            Test{
+                fx: |x| {x.sin()},
+                x: 1.0,
+                n: 2,
+                order: None,
+                expect: -1.0_f64.sin(),
+                precision: 1.0e-9,
+            },
+            Test{
+                fx: |x| {x.sin()},
+                x: 1.0,
+                n: 3,
+                order: None,
+                expect: -1.0_f64.cos(),
+                precision: 1.0e-7,
+            },
+            Test{
+                fx: |x| {x.sin()},
+                x: 1.0,
+                n: 4,
+                order: None,
+                expect: 1.0_f64.sin(),
+                precision: 1.0e-5,
+            },
+            Test{
+                fx: |x| {x.exp()},
+                x: 0.0,
+                n: 2,
+                order: Some(8),
+                expect: 1.0,
+                precision: 1.0e-11,
+            },
+            Test{
+                fx: |x| {x.powf(5.0)},
+                x: 2.0,
+                n: 3,
+                order: Some(2),
+                expect: 240.0,
+                precision: 1.0e-3,
+            },
+            Test{
+                // odd orders are rounded up, 3 works as 4
+                fx: |x| {x.powf(5.0)},
+                x: 2.0,
+                n: 4,
+                order: Some(3),
+                expect: 240.0,
+                precision: 1.0e-2,
+            },
+            Test{
+                fx: |x| {x.ln()},
+                x: 100.0,
+                n: 2,
+                order: None,
+                expect: -1.0e-4,
+                precision: 1.0e-12,
+            },
+            Test{
+                fx: |x| {x.cos()},
+                x: 0.5,
+                n: 0,
+                order: None,
+                expect: 0.5_f64.cos(),
+                precision: 0.0,
+            },
+        ];
+
+        for test in tests {
+            let derivative = nth_derivative(test.fx, test.x, test.n, test.order);
+            assert!((derivative - test.expect).abs() <= test.precision, "{} {}", derivative, test.expect);
+        }
+
+        // a higher order gets closer
+        let low = nth_derivative(|x| x.exp(), 1.0, 2, Some(2));
+        let high = nth_derivative(|x| x.exp(), 1.0, 2, Some(6));
+        assert!((high - 1.0_f64.exp()).abs() < (low - 1.0_f64.exp()).abs());
+    }
+
+    #[test]
+    fn test_one_sided_derivatives() {
+        struct Test {
+            fx: fn(f64) -> f64,
+            x: f64,
+            n: usize,
+            domain: (f64, f64),
+            expect: f64,
+            precision: f64,
+        }
+
+        let tests = vec![
+            Test{
+                // sin(x) written through sqrt, so it is NaN for x < 0
+                fx: |x| {x.sqrt().powi(2).sin()},
+                x: 0.0,
+                n: 1,
+                domain: (0.0, f64::INFINITY),
+                expect: 1.0,
+                precision: 1.0e-9,
+            },
+            Test{
+                fx: |x| {x.sqrt().powi(2).sin()},
+                x: 0.0,
+                n: 2,
+                domain: (0.0, f64::INFINITY),
+                expect: 0.0,
+                precision: 1.0e-6,
+            },
+            Test{
+                fx: |x| {(1.0 - x).sqrt().powi(2).exp()},
+                x: 1.0,
+                n: 1,
+                domain: (f64::NEG_INFINITY, 1.0),
+                expect: -1.0,
+                precision: 1.0e-9,
+            },
+            Test{
+                // right at the edge of a narrow domain, the step has to shrink to fit
+                fx: |x| {(1.0 - x*x).sqrt().powi(2)},
+                x: -1.0,
+                n: 1,
+                domain: (-1.0, -0.999),
+                expect: 2.0,
+                precision: 1.0e-8,
+            },
+        ];
+
+        for test in tests {
+            // the central stencil steps out of the domain
+            assert!(nth_derivative(test.fx, test.x, test.n, None).is_nan());
+
+            let derivative = nth_derivative_in_domain(test.fx, test.x, test.n, None, test.domain);
+            assert!((derivative - test.expect).abs() < test.precision, "{} {}", derivative, test.expect);
+        }
+
+        // well inside the domain it's the central stencil
+        assert_eq!(nth_derivative_in_domain(|x| x.ln(), 2.0, 1, None, (0.0, f64::INFINITY)), nth_derivative(|x| x.ln(), 2.0, 1, None));
+        assert!(nth_derivative_in_domain(|x| x.ln(), -1.0, 1, None, (0.0, f64::INFINITY)).is_nan());
+        assert!(nth_derivative_in_domain(|x| x.ln(), 1.0, 1, None, (1.0, 1.0)).is_nan());
+
+        // a few floats inside a finite end the step doesn't shrink, it only has to fit on the side
+        // the stencil samples
+        let e = 1.0_f64.exp();
+        for lo in [1.0, 1.0 - f64::EPSILON, 1.0 - 4.0*f64::EPSILON, 1.0 - 1.0e-15, 1.0 - 1.0e-12] {
+            let forward = nth_derivative_in_domain(|x| x.exp(), 1.0, 1, None, (lo, f64::INFINITY));
+            assert!((forward - e).abs() < 1.0e-10, "{} {}", lo, forward);
+
+            let backward = nth_derivative_in_domain(|x| x.exp(), -1.0, 1, None, (f64::NEG_INFINITY, -lo));
+            assert!((backward - 1.0/e).abs() < 1.0e-10, "{} {}", lo, backward);
+        }
+
+        for lo in [100.0, 100.0_f64.next_down(), 100.0 - 1.0e-13] {
+            let second = nth_derivative_in_domain(|x| x*x, 100.0, 2, None, (lo, 200.0));
+            assert!((second - 2.0).abs() < 1.0e-6, "{} {}", lo, second);
+        }
+
+        // and close to an end at 0 it shrinks with x
+        for x in [1.0e-1, 1.0e-3, 1.0e-4, 1.0e-6, 1.0e-9] {
+            let ln = nth_derivative_in_domain(|x| x.ln(), x, 1, None, (0.0, f64::INFINITY));
+            assert!((ln*x - 1.0).abs() < 1.0e-9, "{} {}", x, ln);
+
+            let ln2 = nth_derivative_in_domain(|x| x.ln(), x, 2, None, (0.0, f64::INFINITY));
+            assert!((ln2*x*x + 1.0).abs() < 1.0e-6, "{} {}", x, ln2);
+
+            let sqrt = nth_derivative_in_domain(|x| x.sqrt(), x, 1, None, (0.0, f64::INFINITY));
+            assert!((sqrt*2.0*x.sqrt() - 1.0).abs() < 1.0e-9, "{} {}", x, sqrt);
+        }
+
+        // both sides give the same thing on a function defined everywhere
+        for stencil in [Stencil::Forward, Stencil::Backward] {
+            for (n, order) in [(1, 2), (1, 4), (2, 3), (3, 4)] {
+                let derivative = nth_derivative_with_stencil(|x| x.exp(), 0.0, n, Some(order), stencil);
+                assert!((derivative - 1.0).abs() < 1.0e-4, "{:?} {} {} {}", stencil, n, order, derivative);
+            }
+        }
+    }
+
+    #[test]
+    fn test_finite_difference_weights() {
+        struct Test {
+            offsets: Vec<f64>,
+            n: usize,
+            expect: Vec<f64>,
+        }
+
+        let tests = vec![
+            Test{
+                offsets: vec![-1.0, 0.0, 1.0],
+                n: 1,
+                expect: vec![-0.5, 0.0, 0.5],
+            },
+            Test{
+                offsets: vec![-1.0, 0.0, 1.0],
+                n: 2,
+                expect: vec![1.0, -2.0, 1.0],
+            },
+            Test{
+                offsets: vec![-2.0, -1.0, 0.0, 1.0, 2.0],
+                n: 1,
+                expect: vec![1.0/12.0, -2.0/3.0, 0.0, 2.0/3.0, -1.0/12.0],
+            },
+            Test{
+                offsets: vec![-2.0, -1.0, 0.0, 1.0, 2.0],
+                n: 4,
+                expect: vec![1.0, -4.0, 6.0, -4.0, 1.0],
+            },
+            Test{
+                // one sided
+                offsets: vec![0.0, 1.0, 2.0],
+                n: 1,
+                expect: vec![-1.5, 2.0, -0.5],
+            },
+            Test{
+                // uneven, and out of order
+                offsets: vec![2.0, 0.0, 0.5],
+                n: 1,
+                expect: vec![-1.0/6.0, -2.5, 8.0/3.0],
+            },
+            Test{
+                // the 0th derivative is interpolation
+                offsets: vec![-1.0, 1.0],
+                n: 0,
+                expect: vec![0.5, 0.5],
+            },
+            Test{
+                // not enough points for a second derivative
+                offsets: vec![0.0, 1.0],
+                n: 2,
+                expect: vec![0.0, 0.0],
+            },
+        ];
+
+        for test in tests {
+            let weights = finite_difference_weights(&test.offsets, test.n);
+            assert_eq!(weights.len(), test.expect.len());
+
+            for (weight, expect) in weights.iter().zip(&test.expect) {
+                assert!((weight - expect).abs() < 1.0e-13, "{:?} {:?}", weights, test.expect);
+            }
+        }
+
+        // on any offsets the stencil is exact for the polynomials it can fit, here the second
+        // derivative of x^3 - 2x^2 at 0.3, which is 6*0.3 - 4
+        let offsets = [-0.7, -0.1, 0.4, 1.3];
+        let weights = finite_difference_weights(&offsets, 2);
+        let derivative: f64 = offsets.iter().zip(&weights).map(|(k, w)| w*((0.3 + k).powf(3.0) - 2.0*(0.3 + k).powf(2.0))).sum();
+        assert!((derivative - (6.0*0.3 - 4.0)).abs() < 1.0e-12);
+    }
+
     #[test]
     fn test_calculate_integral_with_rectangles() {
         struct Test {
@@ -131,4 +758,97 @@ mod tests {
             assert!((integral - test.expect).abs() < precision);
         }
     }
+
+    #[test]
+    fn test_closures_with_captured_parameters() {
+        let a = 3.0;
+        let b = -2.0;
+
+        let derivative = calculate_derivative(|x| a*x.powf(2.0) + b*x, 2.0);
+        assert!((derivative - 10.0).abs() < 1.0e-9);
+
+        let mut evaluations = 0;
+        let integral = calculate_integral_with_rectangles(|x| {
+            evaluations += 1;
+            a*x.powf(2.0) + b*x
+        }, 0.0, 1.0, Some(1000));
+
+        assert!((integral - 0.0).abs() < 1.0e-6);
+        assert_eq!(evaluations, 1000);
+    }
+
+    #[test]
+    fn test_calculate_jacobian() {
+        struct Test {
+            fx: fn(&[f64]) -> Vec<f64>,
+            x: Vec<f64>,
+            expect: Vec<Vec<f64>>,
+        }
+
+        let tests = vec![
+            Test{
+                fx: |x| {vec![x[0]*x[1], x[0] + 2.0*x[1]]},
+                x: vec![3.0, 4.0],
+                expect: vec![vec![4.0, 3.0], vec![1.0, 2.0]],
+            },
+            Test{
+                fx: |x| {vec![x[0].powf(2.0) + x[1].powf(3.0) - x[2]]},
+                x: vec![1.0, 2.0, 3.0],
+                expect: vec![vec![2.0, 12.0, -1.0]],
+            },
+            Test{
+                fx: |x| {vec![x[0].sin(), x[0].cos(), x[0].exp()]},
+                x: vec![0.0],
+                expect: vec![vec![1.0], vec![0.0], vec![1.0]],
+            },
+        ];
+
+        let precision = 1.0e-9;
+
+        for test in tests {
+            let jacobian = calculate_jacobian(test.fx, &test.x);
+            assert_eq!(jacobian.len(), test.expect.len());
+
+            for (row, expect) in jacobian.iter().zip(&test.expect) {
+                assert_eq!(row.len(), expect.len());
+                for (value, expect) in row.iter().zip(expect) {
+                    assert!((value - expect).abs() < precision);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_calculate_complex_derivative() {
+        struct Test {
+            fx: fn(Complex) -> Complex,
+            z: Complex,
+            expect: Complex,
+        }
+
+        let tests = vec![
+            Test{
+                fx: |z| {z*z},
+                z: Complex::new(1.0, 2.0),
+                expect: Complex::new(2.0, 4.0),
+            },
+            Test{
+                fx: |z| {z.exp()},
+                z: Complex::new(0.0, std::f64::consts::PI),
+                expect: Complex::new(-1.0, 0.0),
+            },
+            Test{
+                fx: |z| {z.powi(3) - 2.0*z},
+                z: Complex::new(0.0, 1.0),
+                expect: Complex::new(-5.0, 0.0),
+            },
+        ];
+
+        let precision = 1.0e-9;
+
+        for test in tests {
+            let derivative = calculate_complex_derivative(test.fx, test.z);
+            assert!((derivative - test.expect).abs() < precision);
+        }
+    }
 }
@@ -0,0 +1,657 @@
+use std::ops::{Add, Mul, Neg, Sub};
+
+use crate::*;
+
+// a polynomial with real coefficients, stored from the lowest power up, so
+// Polynomial::new(vec![-6.0, 11.0, -6.0, 1.0]) is x^3 - 6x^2 + 11x - 6. Trailing zeros are
+// dropped, so the last coefficient is never zero and the zero polynomial has no coefficients
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Polynomial {
+    coefficients: Vec<f64>,
+}
+
+// the most iterations aberth's method gets, simple roots converge cubically so this is only ever
+// reached with multiple roots, which converge linearly
+const MAX_ABERTH_ITERATIONS: usize = 500;
+
+// newton from a good estimate of a simple root is done in a handful of steps
+const MAX_POLISH_ITERATIONS: usize = 20;
+
+// when building a sturm chain the remainders are computed in floating point, so the coefficients
+// that should have cancelled out come back as tiny numbers instead of zero. Each polynomial in the
+// chain is scaled so its largest coefficient is about 1, and the leading ones below this are dropped
+const STURM_TOL: f64 = 1.0e-12;
+
+// where isolate_real_roots splits an interval, slightly off the middle. The sign of p right on a
+// root is just rounding noise, and splitting in halves lands exactly on nice roots like 1 or 2 all
+// the time
+const STURM_SPLIT: f64 = 0.4813;
+
+impl Polynomial {
+    pub fn new(mut coefficients: Vec<f64>) -> Polynomial {
+        while coefficients.last() == Some(&0.0) {
+            coefficients.pop();
+        }
+
+        Polynomial{coefficients}
+    }
+
+    // the monic polynomial (x - r1)(x - r2)... with the given roots
+    pub fn from_roots(roots: &[f64]) -> Polynomial {
+        roots.iter().fold(Polynomial::new(vec![1.0]), |p, &root| p*Polynomial::new(vec![-root, 1.0]))
+    }
+
+    pub fn coefficients(&self) -> &[f64] {
+        &self.coefficients
+    }
+
+    // the degree of the polynomial, which we take as 0 for the zero polynomial too
+    pub fn degree(&self) -> usize {
+        self.coefficients.len().saturating_sub(1)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.coefficients.is_empty()
+    }
+
+    // evaluates the polynomial at x with horner's scheme
+    pub fn eval(&self, x: f64) -> f64 {
+        self.coefficients.iter().rev().fold(0.0, |acc, &c| acc*x + c)
+    }
+
+    // same as eval, but at a complex point
+    pub fn eval_complex(&self, z: Complex) -> Complex {
+        self.coefficients.iter().rev().fold(Complex::ZERO, |acc, &c| acc*z + c)
+    }
+
+    pub fn derivative(&self) -> Polynomial {
+        let coefficients = self.coefficients.iter()
+            .enumerate()
+            .skip(1)
+            .map(|(i, &c)| i as f64*c)
+            .collect();
+
+        Polynomial::new(coefficients)
+    }
+
+    // the antiderivative that vanishes at x = 0
+    pub fn antiderivative(&self) -> Polynomial {
+        let mut coefficients = vec![0.0];
+        coefficients.extend(self.coefficients.iter().enumerate().map(|(i, &c)| c/(i + 1) as f64));
+
+        Polynomial::new(coefficients)
+    }
+
+    // calculates all the complex roots of the polynomial, repeated as many times as their
+    // multiplicity, with the aberth-ehrlich method: every root estimate takes a newton step that
+    // is pushed away from all the other estimates, so they converge to different roots at once.
+    // Simple roots come out to full precision, a root of multiplicity m only to about 1e-16^(1/m),
+    // use all_roots_with_multiplicity to get those back together
+    pub fn all_roots(&self) -> Vec<Complex> {
+        // roots at zero are taken out by hand, they are exact and would only slow aberth down
+        let zeros = self.coefficients.iter().take_while(|&&c| c == 0.0).count();
+        let mut roots = vec![Complex::ZERO; zeros.min(self.degree())];
+
+        let p = Polynomial::new(self.coefficients[zeros.min(self.coefficients.len())..].to_vec());
+        let n = p.degree();
+        if n == 0 {
+            return roots;
+        }
+
+        let dp = p.derivative();
+        let lead = p.coefficients[n];
+
+        // fujiwara's bound, every root has a modulus below this
+        let radius = (1..=n)
+            .map(|k| {
+                let c = (p.coefficients[n - k]/lead).abs();
+                if k == n { (c/2.0).powf(1.0/k as f64) } else { c.powf(1.0/k as f64) }
+            })
+            .fold(0.0, f64::max)*2.0;
+
+        // the starting points are spread on a circle, the 0.4 offset keeps them off the real axis
+        // so conjugate pairs can split
+        let mut z: Vec<Complex> = (0..n)
+            .map(|k| Complex::from_polar(radius, 2.0*std::f64::consts::PI*k as f64/n as f64 + 0.4))
+            .collect();
+
+        for _ in 0..MAX_ABERTH_ITERATIONS {
+            let mut converged = true;
+
+            for i in 0..n {
+                let pz = p.eval_complex(z[i]);
+                let sum = (0..n)
+                    .filter(|&j| j != i)
+                    .fold(Complex::ZERO, |acc, j| acc + Complex::ONE/(z[i] - z[j]));
+
+                let w = pz/(dp.eval_complex(z[i]) - pz*sum);
+                if !w.is_finite() {
+                    continue;
+                }
+
+                z[i] -= w;
+                if w.abs() > 4.0*f64::EPSILON*z[i].abs() {
+                    converged = false;
+                }
+            }
+
+            if converged {
+                break;
+            }
+        }
+
+        roots.extend(z);
+        roots
+    }
+
+    // same as all_roots, but the roots closer than tol*max(1, |root|) to each other are taken as a
+    // single root and come back once, with how many times it was found. The tolerance defaults to
+    // 1e-3, since the estimates of a root of multiplicity 4 are already spread by ~1e-4, so pass a
+    // smaller one if you have distinct roots that close. A root of multiplicity m is a simple root
+    // of the (m-1)th derivative, so the average of each cluster is polished with newton on that,
+    // which gets it back to full precision
+    pub fn all_roots_with_multiplicity(&self, tol: Option<f64>) -> Vec<(Complex, usize)> {
+        let tol = tol.unwrap_or(1.0e-3);
+        let mut roots = self.all_roots();
+        let mut result = Vec::new();
+
+        while let Some(root) = roots.pop() {
+            let (cluster, rest): (Vec<Complex>, Vec<Complex>) = roots.into_iter()
+                .partition(|&z| (z - root).abs() <= tol*root.abs().max(1.0));
+            roots = rest;
+
+            let multiplicity = cluster.len() + 1;
+            let mean = cluster.into_iter().fold(root, |acc, z| acc + z)/multiplicity as f64;
+
+            let q = (1..multiplicity).fold(self.clone(), |q, _| q.derivative());
+            result.push((q.newton_polish(mean), multiplicity));
+        }
+
+        result.sort_by(|a, b| a.0.re.total_cmp(&b.0.re).then(a.0.im.total_cmp(&b.0.im)));
+        result
+    }
+
+    // divides the polynomial by divisor, returning the quotient and the remainder. Panics if the
+    // divisor is the zero polynomial
+    pub fn div_rem(&self, divisor: &Polynomial) -> (Polynomial, Polynomial) {
+        assert!(!divisor.is_zero(), "division by the zero polynomial");
+
+        let n = divisor.degree();
+        let lead = divisor.coefficients[n];
+        let mut remainder = self.coefficients.clone();
+
+        if remainder.len() <= n {
+            return (Polynomial::default(), self.clone());
+        }
+
+        let mut quotient = vec![0.0; remainder.len() - n];
+        for i in (0..quotient.len()).rev() {
+            let q = remainder[i + n]/lead;
+            quotient[i] = q;
+
+            for (j, d) in divisor.coefficients.iter().enumerate() {
+                remainder[i + j] -= q*d;
+            }
+            remainder[i + n] = 0.0;
+        }
+
+        (Polynomial::new(quotient), Polynomial::new(remainder))
+    }
+
+    // the sturm chain p, p', -rem(p, p'), ... which stops at the gcd of p and p'. Every polynomial
+    // in it is scaled by a positive number, which doesn't change any of the signs
+    pub fn sturm_sequence(&self) -> Vec<Polynomial> {
+        let mut chain = vec![self.normalized()];
+        if self.degree() == 0 {
+            return chain;
+        }
+
+        let mut next = self.derivative().normalized();
+        while !next.is_zero() {
+            let (_, remainder) = chain[chain.len() - 1].div_rem(&next);
+            chain.push(next);
+            next = -remainder.trimmed(STURM_TOL).normalized();
+        }
+
+        chain
+    }
+
+    // counts the distinct real roots in (a, b] with sturm's theorem, the number of sign changes
+    // along the sturm chain at a minus the ones at b. Either end can be infinite, so
+    // count_real_roots(f64::NEG_INFINITY, f64::INFINITY) counts all of them
+    pub fn count_real_roots(&self, a: f64, b: f64) -> usize {
+        let chain = self.sturm_sequence();
+        sign_changes(&chain, a).saturating_sub(sign_changes(&chain, b))
+    }
+
+    // splits (a, b] into intervals that contain exactly one distinct real root each, by bisecting
+    // until the sturm counts are all 0 or 1. Infinite ends are replaced by cauchy's bound on the
+    // roots. The intervals come back sorted. Like any floating point evaluation, a root sitting
+    // exactly on a or b may or may not be counted
+    pub fn isolate_real_roots(&self, a: f64, b: f64) -> Vec<(f64, f64)> {
+        let chain = self.sturm_sequence();
+        let bound = self.root_bound();
+        let (a, b) = (a.max(-bound), b.min(bound));
+
+        let mut intervals = Vec::new();
+        let mut pending = vec![(a, b, sign_changes(&chain, a), sign_changes(&chain, b))];
+
+        while let Some((a, b, va, vb)) = pending.pop() {
+            let count = va.saturating_sub(vb);
+            let mid = a + (b - a)*STURM_SPLIT;
+
+            if count == 0 {
+                continue;
+            } else if count == 1 || mid <= a || mid >= b {
+                // the second case only happens with roots closer than a f64 can tell apart
+                intervals.push((a, b));
+            } else {
+                let vm = sign_changes(&chain, mid);
+                pending.push((mid, b, vm, vb));
+                pending.push((a, mid, va, vm));
+            }
+        }
+
+        intervals.sort_by(|x, y| x.0.total_cmp(&y.0));
+        intervals
+    }
+
+    // calculates every distinct real root in (a, b], with no risk of missing close roots like
+    // bissec_root_many can, since the sturm counts tell exactly how many there are. The isolating
+    // intervals are refined with bissec_root. The roots of even multiplicity don't change sign, so
+    // those are refined on the square free part of the polynomial instead, p divided by
+    // gcd(p, p'), which has the same roots but all simple. The zero polynomial vanishes everywhere,
+    // so like all_roots it gives no roots at all
+    pub fn real_roots<O>(&self, a: f64, b: f64, options: O) -> Vec<f64>
+    where
+        O: Into<SolverOptions>,
+    {
+        let options = options.into();
+        if self.is_zero() {
+            return Vec::new();
+        }
+
+        let chain = self.sturm_sequence();
+        let gcd = &chain[chain.len() - 1];
+        let square_free = self.div_rem(gcd).0;
+
+        self.isolate_real_roots(a, b)
+            .into_iter()
+            .filter_map(|(a, b)| {
+                if self.eval(a).signum() != self.eval(b).signum() || self.eval(b) == 0.0 {
+                    bissec_root(|x| self.eval(x), a, b, options)
+                } else {
+                    bissec_root(|x| square_free.eval(x), a, b, options)
+                }
+            })
+            .collect()
+    }
+
+    // cauchy's bound, every root has a modulus below this
+    fn root_bound(&self) -> f64 {
+        let n = self.degree();
+        let lead = self.coefficients.get(n).copied().unwrap_or(1.0);
+        1.0 + self.coefficients[..n].iter().map(|c| (c/lead).abs()).fold(0.0, f64::max)
+    }
+
+    // scaled by a power of two (so no rounding happens) until the largest coefficient is about 1
+    fn normalized(&self) -> Polynomial {
+        let scale = self.coefficients.iter().map(|c| c.abs()).fold(0.0, f64::max);
+        if scale == 0.0 {
+            return self.clone();
+        }
+
+        self*2.0_f64.powi(-scale.log2().round() as i32)
+    }
+
+    // drops the leading coefficients below tol
+    fn trimmed(mut self, tol: f64) -> Polynomial {
+        while self.coefficients.last().is_some_and(|c| c.abs() <= tol) {
+            self.coefficients.pop();
+        }
+
+        self
+    }
+
+    // a few complex newton steps from z, stopping as soon as they don't make |p(z)| smaller
+    fn newton_polish(&self, mut z: Complex) -> Complex {
+        let dp = self.derivative();
+        let mut residual = self.eval_complex(z).abs();
+
+        for _ in 0..MAX_POLISH_ITERATIONS {
+            let next = z - self.eval_complex(z)/dp.eval_complex(z);
+            let next_residual = self.eval_complex(next).abs();
+
+            if !next.is_finite() || next_residual >= residual {
+                break;
+            }
+
+            z = next;
+            residual = next_residual;
+        }
+
+        z
+    }
+}
+
+// the number of sign changes along the sturm chain at x, zeros don't count. At infinity the sign
+// of each polynomial is the one of its leading term
+fn sign_changes(chain: &[Polynomial], x: f64) -> usize {
+    let signs = chain.iter().map(|p| {
+        if x.is_infinite() {
+            let lead = p.coefficients.last().copied().unwrap_or(0.0);
+            if p.degree() % 2 == 1 { lead*x.signum() } else { lead }
+        } else {
+            p.eval(x)
+        }
+    });
+
+    let mut changes = 0;
+    let mut last = 0.0;
+
+    for sign in signs.filter(|&v| v != 0.0) {
+        if last != 0.0 && sign.signum() != last {
+            changes += 1;
+        }
+        last = sign.signum();
+    }
+
+    changes
+}
+
+impl Neg for Polynomial {
+    type Output = Polynomial;
+
+    fn neg(self) -> Polynomial {
+        self*-1.0
+    }
+}
+
+impl Add for &Polynomial {
+    type Output = Polynomial;
+
+    fn add(self, other: &Polynomial) -> Polynomial {
+        let n = self.coefficients.len().max(other.coefficients.len());
+        let coefficients = (0..n)
+            .map(|i| self.coefficients.get(i).unwrap_or(&0.0) + other.coefficients.get(i).unwrap_or(&0.0))
+            .collect();
+
+        Polynomial::new(coefficients)
+    }
+}
+
+impl Sub for &Polynomial {
+    type Output = Polynomial;
+
+    fn sub(self, other: &Polynomial) -> Polynomial {
+        self + &(other*-1.0)
+    }
+}
+
+impl Mul for &Polynomial {
+    type Output = Polynomial;
+
+    fn mul(self, other: &Polynomial) -> Polynomial {
+        if self.is_zero() || other.is_zero() {
+            return Polynomial::default();
+        }
+
+        let mut coefficients = vec![0.0; self.coefficients.len() + other.coefficients.len() - 1];
+        for (i, a) in self.coefficients.iter().enumerate() {
+            for (j, b) in other.coefficients.iter().enumerate() {
+                coefficients[i + j] += a*b;
+            }
+        }
+
+        Polynomial::new(coefficients)
+    }
+}
+
+impl Mul<f64> for &Polynomial {
+    type Output = Polynomial;
+
+    fn mul(self, other: f64) -> Polynomial {
+        Polynomial::new(self.coefficients.iter().map(|c| c*other).collect())
+    }
+}
+
+// the owned versions just borrow and forward to the ones above
+macro_rules! impl_polynomial_ops {
+    ($($trait:ident $method:ident),*) => {$(
+        impl $trait for Polynomial {
+            type Output = Polynomial;
+
+            fn $method(self, other: Polynomial) -> Polynomial {
+                $trait::$method(&self, &other)
+            }
+        }
+    )*};
+}
+
+impl_polynomial_ops!(Add add, Sub sub, Mul mul);
+
+impl Mul<f64> for Polynomial {
+    type Output = Polynomial;
+
+    fn mul(self, other: f64) -> Polynomial {
+        &self*other
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+//
+// CRATE TESTS
+//
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_polynomial_arithmetic() {
+        struct Test {
+            result: Polynomial,
+            expect: Vec<f64>,
+        }
+
+        let p = Polynomial::new(vec![-6.0, 11.0, -6.0, 1.0]);
+        let q = Polynomial::new(vec![1.0, 1.0]);
+
+        let tests = vec![
+            Test{
+                result: Polynomial::from_roots(&[1.0, 2.0, 3.0]),
+                expect: vec![-6.0, 11.0, -6.0, 1.0],
+            },
+            Test{
+                result: &p + &q,
+                expect: vec![-5.0, 12.0, -6.0, 1.0],
+            },
+            Test{
+                result: &p - &p,
+                expect: vec![],
+            },
+            Test{
+                result: &p*&q,
+                expect: vec![-6.0, 5.0, 5.0, -5.0, 1.0],
+            },
+            Test{
+                result: -q.clone()*2.0,
+                expect: vec![-2.0, -2.0],
+            },
+            Test{
+                result: p.derivative(),
+                expect: vec![11.0, -12.0, 3.0],
+            },
+            Test{
+                result: p.derivative().antiderivative(),
+                expect: vec![0.0, 11.0, -6.0, 1.0],
+            },
+            Test{
+                result: Polynomial::new(vec![1.0, 0.0, 0.0]),
+                expect: vec![1.0],
+            },
+        ];
+
+        for test in tests {
+            assert_eq!(test.result.coefficients(), test.expect.as_slice());
+        }
+
+        assert_eq!(p.degree(), 3);
+        assert_eq!(p.eval(4.0), 6.0);
+        assert_eq!(p.eval_complex(Complex::new(4.0, 0.0)), Complex::new(6.0, 0.0));
+        assert!(Polynomial::new(vec![0.0]).is_zero());
+    }
+
+    #[test]
+    fn test_all_roots() {
+        struct Test {
+            p: Polynomial,
+            expect: Vec<(Complex, usize)>,
+        }
+
+        let tests = vec![
+            Test{
+                p: Polynomial::new(vec![-6.0, 11.0, -6.0, 1.0]),
+                expect: vec![(Complex::new(1.0, 0.0), 1), (Complex::new(2.0, 0.0), 1), (Complex::new(3.0, 0.0), 1)],
+            },
+            Test{
+                p: Polynomial::new(vec![1.0, 0.0, 1.0]),
+                expect: vec![(Complex::new(0.0, -1.0), 1), (Complex::new(0.0, 1.0), 1)],
+            },
+            Test{
+                p: Polynomial::new(vec![0.0, 0.0, -1.0, 1.0]),
+                expect: vec![(Complex::new(0.0, 0.0), 2), (Complex::new(1.0, 0.0), 1)],
+            },
+            Test{
+                p: Polynomial::from_roots(&[2.0, 2.0, 2.0, -1.0]),
+                expect: vec![(Complex::new(-1.0, 0.0), 1), (Complex::new(2.0, 0.0), 3)],
+            },
+            Test{
+                p: Polynomial::from_roots(&[1.0, 1.0, 1.0, 1.0]),
+                expect: vec![(Complex::new(1.0, 0.0), 4)],
+            },
+            Test{
+                // x^5 - 1, the fifth roots of unity
+                p: Polynomial::new(vec![-1.0, 0.0, 0.0, 0.0, 0.0, 1.0]),
+                expect: vec![
+                    (Complex::from_polar(1.0, 4.0*std::f64::consts::PI/5.0).conj(), 1),
+                    (Complex::from_polar(1.0, 4.0*std::f64::consts::PI/5.0), 1),
+                    (Complex::from_polar(1.0, 2.0*std::f64::consts::PI/5.0).conj(), 1),
+                    (Complex::from_polar(1.0, 2.0*std::f64::consts::PI/5.0), 1),
+                    (Complex::ONE, 1),
+                ],
+            },
+            Test{
+                p: Polynomial::new(vec![5.0]),
+                expect: vec![],
+            },
+        ];
+
+        for test in tests {
+            let roots = test.p.all_roots();
+            assert_eq!(roots.len(), test.p.degree());
+
+            for root in roots {
+                assert!(test.p.eval_complex(root).abs() < 1.0e-9);
+            }
+
+            let roots = test.p.all_roots_with_multiplicity(None);
+            assert_eq!(roots.len(), test.expect.len());
+
+            for ((root, multiplicity), (expect, expect_multiplicity)) in roots.into_iter().zip(test.expect) {
+                assert!((root - expect).abs() < 1.0e-10, "{} {}", root, expect);
+                assert_eq!(multiplicity, expect_multiplicity);
+            }
+        }
+    }
+
+    #[test]
+    fn test_div_rem() {
+        let p = Polynomial::new(vec![-6.0, 11.0, -6.0, 1.0]);
+
+        let (quotient, remainder) = p.div_rem(&Polynomial::new(vec![-1.0, 1.0]));
+        assert_eq!(quotient.coefficients(), &[6.0, -5.0, 1.0]);
+        assert!(remainder.is_zero());
+
+        let (quotient, remainder) = p.div_rem(&Polynomial::new(vec![0.0, 0.0, 2.0]));
+        assert_eq!(quotient.coefficients(), &[-3.0, 0.5]);
+        assert_eq!(remainder.coefficients(), &[-6.0, 11.0]);
+
+        let (quotient, remainder) = Polynomial::new(vec![1.0, 1.0]).div_rem(&p);
+        assert!(quotient.is_zero());
+        assert_eq!(remainder.coefficients(), &[1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_sturm_real_roots() {
+        struct Test {
+            p: Polynomial,
+            a: f64,
+            b: f64,
+            expect: Vec<f64>,
+        }
+
+        let tests = vec![
+            Test{
+                p: Polynomial::new(vec![-6.0, 11.0, -6.0, 1.0]),
+                a: 0.0,
+                b: 4.0,
+                expect: vec![1.0, 2.0, 3.0],
+            },
+            Test{
+                p: Polynomial::new(vec![-6.0, 11.0, -6.0, 1.0]),
+                a: 1.5,
+                b: 3.0,
+                expect: vec![2.0, 3.0],
+            },
+            Test{
+                // the double root at 1 doesn't change sign, and still counts once
+                p: Polynomial::from_roots(&[1.0, 1.0, 3.0]),
+                a: f64::NEG_INFINITY,
+                b: f64::INFINITY,
+                expect: vec![1.0, 3.0],
+            },
+            Test{
+                // close enough that a regular scan of the interval misses them, and badly
+                // conditioned enough that they only come out to about 1e-9
+                p: Polynomial::from_roots(&[1.0, 1.001, 1.002, 5.0]),
+                a: 0.0,
+                b: 10.0,
+                expect: vec![1.0, 1.001, 1.002, 5.0],
+            },
+            Test{
+                p: Polynomial::new(vec![1.0, 0.0, 1.0]),
+                a: f64::NEG_INFINITY,
+                b: f64::INFINITY,
+                expect: vec![],
+            },
+        ];
+
+        for test in tests {
+            assert_eq!(test.p.count_real_roots(test.a, test.b), test.expect.len());
+
+            let intervals = test.p.isolate_real_roots(test.a, test.b);
+            assert_eq!(intervals.len(), test.expect.len());
+
+            let roots = test.p.real_roots(test.a, test.b, None);
+            assert_eq!(roots.len(), test.expect.len());
+
+            for ((root, (a, b)), expect) in roots.iter().zip(intervals).zip(&test.expect) {
+                assert!(a < *expect && expect <= &b);
+                assert!((root - expect).abs() < 1.0e-8, "{} {}", root, expect);
+            }
+        }
+
+        let p = Polynomial::from_roots(&[1.0, 1.001, 1.002, 5.0]);
+        assert_eq!(bissec_root_many(|x| p.eval(x), 0.0, 10.0, 10, None).len(), 2);
+        assert_eq!(bissec_root_many_polynomial(&p, 0.0, 10.0, None).len(), 4);
+
+        // the zero polynomial has no isolated roots to give
+        let zero = Polynomial::new(vec![]);
+        assert_eq!(zero.count_real_roots(f64::NEG_INFINITY, f64::INFINITY), 0);
+        assert!(zero.isolate_real_roots(-1.0, 1.0).is_empty());
+        assert!(zero.real_roots(-1.0, 1.0, None).is_empty());
+        assert!(bissec_root_many_polynomial(&zero, -1.0, 1.0, None).is_empty());
+    }
+}
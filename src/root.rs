@@ -1,202 +1,3583 @@
+use std::fmt;
 use std::thread;
-use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use crate::*;
 
-// calculates the root of a certain function at a certain point with an optional given precision
-// sometimes the precision may be too high, and the root never converges to this extreme precise
-// point, so you may have to lower it
-pub fn newton_root(fx: fn(f64) -> f64, xo: f64, precision: Option<f64>) -> f64 {
-    let precision = precision.unwrap_or(0.0e-16);
+// the ways a root solver can fail, returned by the try_ variants of the solvers instead of looping
+// forever or handing back garbage
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RootError {
+    ZeroDerivative,
+    MaxIterations,
+    MaxEvaluations,
+    NonFinite,
+    NoSignChange,
+    Diverged,
+    ZeroOnContour,
+    NotSquare,
+    PolesInside,
+    Stalled,
+    Stopped,
+}
+
+impl fmt::Display for RootError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RootError::ZeroDerivative => write!(f, "the derivative vanished, can't take a newton step"),
+            RootError::MaxIterations => write!(f, "the maximum number of iterations was reached"),
+            RootError::MaxEvaluations => write!(f, "the maximum number of function evaluations was reached"),
+            RootError::NonFinite => write!(f, "the function or the iterate became infinite or NaN"),
+            RootError::NoSignChange => write!(f, "the function doesn't change sign in the interval"),
+            RootError::Diverged => write!(f, "the iterates are moving away from each other"),
+            RootError::ZeroOnContour => write!(f, "the function has a zero on the contour"),
+            RootError::NotSquare => write!(f, "the system doesn't have as many equations as unknowns"),
+            RootError::PolesInside => write!(f, "the function has more poles than zeros inside the contour"),
+            RootError::Stalled => write!(f, "the iterates stopped getting any closer before converging"),
+            RootError::Stopped => write!(f, "the observer stopped the solver before it converged"),
+        }
+    }
+}
+
+impl std::error::Error for RootError {}
+
+// what a successful solve looks like, the root itself, the value of the function there, how many
+// iterations it took and how many times the function was called. The solvers in the complex plane
+// report a RootReport<Complex>
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RootReport<T = f64> {
+    pub root: T,
+    pub residual: T,
+    pub iterations: usize,
+    pub evaluations: usize,
+}
+
+// same as RootReport, but for the solvers of systems of equations, where the root and the value of
+// the function there (the residual) are vectors
+#[derive(Debug, Clone, PartialEq)]
+pub struct SystemReport {
+    pub root: Vec<f64>,
+    pub residual: Vec<f64>,
+    pub iterations: usize,
+    pub evaluations: usize,
+}
+
+// the stopping criteria shared by all the solvers. A solver stops when either the step in x gets
+// below x_abs_tol + x_rel_tol*|x| or when |f(x)| gets below f_abs_tol, and it fails when it runs
+// out of iterations or function evaluations. The old `precision: Option<f64>` argument still
+// works, it converts into the default options with f_abs_tol set to the given precision
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SolverOptions {
+    pub x_abs_tol: f64,
+    pub x_rel_tol: f64,
+    pub f_abs_tol: f64,
+    pub max_iterations: usize,
+    pub max_evaluations: usize,
+}
+
+impl SolverOptions {
+    // true if a step of dx taken from x is small enough to stop
+    pub fn x_converged(&self, x: f64, dx: f64) -> bool {
+        dx.abs() <= self.x_abs_tol + self.x_rel_tol*x.abs()
+    }
+
+    // true if the residual fx is small enough to stop
+    pub fn f_converged(&self, fx: f64) -> bool {
+        fx.abs() <= self.f_abs_tol
+    }
+
+    // the same as x_converged for the solvers of systems, every component has to pass
+    pub fn x_converged_all(&self, x: &[f64], dx: &[f64]) -> bool {
+        x.iter().zip(dx).all(|(&x, &dx)| self.x_converged(x, dx))
+    }
+
+    // the same as f_converged for the solvers of systems, every component has to pass
+    pub fn f_converged_all(&self, fx: &[f64]) -> bool {
+        fx.iter().all(|&fx| self.f_converged(fx))
+    }
+}
+
+// by default we only look at the steps in x, with a relative tolerance of a few ulps and an
+// absolute one for roots sitting at zero. The residual only stops the solver on an exact zero,
+// since its scale depends entirely on the function. The newton-like solvers can spend up to
+// MAX_BACKTRACKS + 3 calls on an iteration that backtracks all the way (two for the estimated
+// derivative and one for each step tried), so the default budget of calls covers that for every
+// iteration. When raising max_iterations raise max_evaluations with it, or the solver runs out of
+// calls first and fails with MaxEvaluations
+impl Default for SolverOptions {
+    fn default() -> SolverOptions {
+        SolverOptions{
+            x_abs_tol: 1.0e-15,
+            x_rel_tol: 4.0*f64::EPSILON,
+            f_abs_tol: 0.0,
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+            max_evaluations: DEFAULT_MAX_ITERATIONS*(MAX_BACKTRACKS + 3),
+        }
+    }
+}
+
+const DEFAULT_MAX_ITERATIONS: usize = 200;
+
+impl From<Option<f64>> for SolverOptions {
+    fn from(precision: Option<f64>) -> SolverOptions {
+        let mut options = SolverOptions::default();
+        if let Some(precision) = precision {
+            options.f_abs_tol = precision;
+        }
+
+        options
+    }
+}
+
+// what an observer gets to see after every iteration of a solver: the new estimate of the root, the
+// value of the function there, the step just taken and, for the bracketing solvers, the bracket
+// left around the root
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Iteration {
+    pub iteration: usize,
+    pub x: f64,
+    pub residual: f64,
+    pub step: f64,
+    pub bracket: Option<(f64, f64)>,
+}
+
+// watches a solver iterate, observe is called after every iteration and returning false stops the
+// solver there. Unless that iteration converged anyway the solver then gives Stopped, the estimate
+// it had is the last one the observer saw. Any closure taking an &Iteration and returning a bool is
+// an observer
+pub trait Observer {
+    fn observe(&mut self, iteration: &Iteration) -> bool;
+}
+
+impl<F> Observer for F
+where
+    F: FnMut(&Iteration) -> bool,
+{
+    fn observe(&mut self, iteration: &Iteration) -> bool {
+        self(iteration)
+    }
+}
+
+// an observer that writes every iteration as a row of a DataFile, so the convergence can be
+// plotted later. The columns are the iteration, the estimate, the residual, the step and the width
+// of the bracket (NaN for the solvers that don't keep one). It never stops the solver
+pub struct TraceLogger {
+    file: DataFile,
+}
+
+impl TraceLogger {
+    pub fn new(file: DataFile) -> TraceLogger {
+        TraceLogger{file}
+    }
+}
+
+impl Observer for TraceLogger {
+    fn observe(&mut self, iteration: &Iteration) -> bool {
+        let width = iteration.bracket.map_or(f64::NAN, |(a, b)| (b - a).abs());
+        self.file.write_row(&[iteration.iteration as f64, iteration.x, iteration.residual, iteration.step, width]);
+
+        true
+    }
+}
+
+// calculates the root of a certain function starting at a certain point. If the solver fails
+// (the derivative vanishes, it diverges or runs out of iterations) it returns NaN, use
+// try_newton_root if you want to know why. The derivative is estimated with calculate_derivative,
+// use newton_root_with_derivative if you know it analytically
+pub fn newton_root<F, O>(fx: F, xo: f64, options: O) -> f64
+where
+    F: FnMut(f64) -> f64,
+    O: Into<SolverOptions>,
+{
+    try_newton_root(fx, xo, options).map(|r| r.root).unwrap_or(f64::NAN)
+}
+
+// same as newton_root, but reports how it went, and why it failed if it did
+pub fn try_newton_root<F, O>(fx: F, xo: f64, options: O) -> Result<RootReport, RootError>
+where
+    F: FnMut(f64) -> f64,
+    O: Into<SolverOptions>,
+{
+    try_newton_root_observed(fx, xo, options, &mut |_: &Iteration| true)
+}
+
+// same as try_newton_root, but the observer gets to see every iteration, and can stop the solver
+// early, in which case the last iterate is reported as the root
+pub fn try_newton_root_observed<F, O, Ob>(fx: F, xo: f64, options: O, observer: &mut Ob) -> Result<RootReport, RootError>
+where
+    F: FnMut(f64) -> f64,
+    O: Into<SolverOptions>,
+    Ob: Observer + ?Sized,
+{
+    let options = options.into();
+    let mut fx = Counted::new(fx, options.max_evaluations);
+
+    newton_iterate(&mut fx, xo, options, observer, |fx, x, root| {
+        newton_step(root, calculate_derivative(|x| fx.eval(x), x))
+    })
+}
+
+// calculates the root of a certain function starting at a certain point, using the given
+// derivative dfx instead of estimating it. Returns NaN if it fails, like newton_root
+pub fn newton_root_with_derivative<F, D, O>(fx: F, dfx: D, xo: f64, options: O) -> f64
+where
+    F: FnMut(f64) -> f64,
+    D: FnMut(f64) -> f64,
+    O: Into<SolverOptions>,
+{
+    try_newton_root_with_derivative(fx, dfx, xo, options).map(|r| r.root).unwrap_or(f64::NAN)
+}
+
+// same as newton_root_with_derivative, but reports how it went, and why it failed if it did. The
+// evaluations in the report only count the calls to fx
+pub fn try_newton_root_with_derivative<F, D, O>(fx: F, dfx: D, xo: f64, options: O) -> Result<RootReport, RootError>
+where
+    F: FnMut(f64) -> f64,
+    D: FnMut(f64) -> f64,
+    O: Into<SolverOptions>,
+{
+    try_newton_root_with_derivative_observed(fx, dfx, xo, options, &mut |_: &Iteration| true)
+}
+
+// same as try_newton_root_with_derivative, with an observer like try_newton_root_observed
+pub fn try_newton_root_with_derivative_observed<F, D, O, Ob>(fx: F, mut dfx: D, xo: f64, options: O, observer: &mut Ob) -> Result<RootReport, RootError>
+where
+    F: FnMut(f64) -> f64,
+    D: FnMut(f64) -> f64,
+    O: Into<SolverOptions>,
+    Ob: Observer + ?Sized,
+{
+    let options = options.into();
+    let mut fx = Counted::new(fx, options.max_evaluations);
+
+    newton_iterate(&mut fx, xo, options, observer, |_, x, root| newton_step(root, dfx(x)))
+}
+
+// calculates the root of a certain function starting at a certain point with halley's method,
+// which also uses the second derivative d2fx and converges cubically instead of quadratically.
+// Returns NaN if it fails, like newton_root
+pub fn halley_root<F, D, D2, O>(fx: F, dfx: D, d2fx: D2, xo: f64, options: O) -> f64
+where
+    F: FnMut(f64) -> f64,
+    D: FnMut(f64) -> f64,
+    D2: FnMut(f64) -> f64,
+    O: Into<SolverOptions>,
+{
+    try_halley_root(fx, dfx, d2fx, xo, options).map(|r| r.root).unwrap_or(f64::NAN)
+}
+
+// same as halley_root, but reports how it went, and why it failed if it did. The evaluations in
+// the report only count the calls to fx
+pub fn try_halley_root<F, D, D2, O>(fx: F, dfx: D, d2fx: D2, xo: f64, options: O) -> Result<RootReport, RootError>
+where
+    F: FnMut(f64) -> f64,
+    D: FnMut(f64) -> f64,
+    D2: FnMut(f64) -> f64,
+    O: Into<SolverOptions>,
+{
+    try_halley_root_observed(fx, dfx, d2fx, xo, options, &mut |_: &Iteration| true)
+}
+
+// same as try_halley_root, with an observer like try_newton_root_observed
+pub fn try_halley_root_observed<F, D, D2, O, Ob>(fx: F, mut dfx: D, mut d2fx: D2, xo: f64, options: O, observer: &mut Ob) -> Result<RootReport, RootError>
+where
+    F: FnMut(f64) -> f64,
+    D: FnMut(f64) -> f64,
+    D2: FnMut(f64) -> f64,
+    O: Into<SolverOptions>,
+    Ob: Observer + ?Sized,
+{
+    let options = options.into();
+    let mut fx = Counted::new(fx, options.max_evaluations);
+
+    newton_iterate(&mut fx, xo, options, observer, |_, x, root| {
+        let derivative = dfx(x);
+        let second = d2fx(x);
+
+        // x - 2ff'/(2f'^2 - ff''), which is the householder method of order 2
+        newton_step(2.0*root*derivative, 2.0*derivative.powf(2.0) - root*second)
+    })
+}
+
+// the maximum number of times a newton step is halved when it makes the residual grow
+const MAX_BACKTRACKS: usize = 30;
+
+// the backtracking of the newton solvers, real or complex: the full step is halved until the value
+// of the function after it (eval gives the value and its size) is finite and no bigger than
+// residual, and if that never happens the smallest step is taken anyway. Gives the step taken and
+// the value there
+fn backtrack<T, V, E>(mut eval: E, full: T, residual: f64) -> (T, V)
+where
+    T: Copy + std::ops::DivAssign<f64>,
+    E: FnMut(T) -> (V, f64),
+{
+    let mut damped = full;
+    let (mut next, mut size) = eval(damped);
+
+    for _ in 0..MAX_BACKTRACKS {
+        if size.is_finite() && size <= residual {
+            break;
+        }
+
+        damped /= 2.0;
+        (next, size) = eval(damped);
+    }
+
+    (damped, next)
+}
+
+// the loop shared by the newton-like solvers, step gives the full correction at x (so the next
+// iterate is x - step) and the rest is the same: the stopping criteria, and the backtracking, the
+// smallest step is taken when nothing better turns up so the solver can still get out of there.
+// The observer sees every iterate
+fn newton_iterate<F, Ob, S>(fx: &mut Counted<F>, xo: f64, options: SolverOptions, observer: &mut Ob, mut step: S) -> Result<RootReport, RootError>
+where
+    F: FnMut(f64) -> f64,
+    Ob: Observer + ?Sized,
+    S: FnMut(&mut Counted<F>, f64, f64) -> Result<f64, RootError>,
+{
+    let mut xn = xo;
+    let mut root = fx.eval(xn);
+
+    for iterations in 0..=options.max_iterations {
+        if !xn.is_finite() || !root.is_finite() {
+            return Err(RootError::NonFinite);
+        }
+
+        if options.f_converged(root) {
+            return Ok(fx.report(xn, root, iterations));
+        }
+
+        if iterations == options.max_iterations {
+            break;
+        }
+        fx.budget()?;
+
+        let full = step(fx, xn, root)?;
+
+        if options.x_converged(xn, full) {
+            xn -= full;
+            root = fx.eval(xn);
+
+            if !root.is_finite() {
+                return Err(RootError::NonFinite);
+            }
+
+            observer.observe(&Iteration{iteration: iterations + 1, x: xn, residual: root, step: full, bracket: None});
+            return Ok(fx.report(xn, root, iterations + 1));
+        }
+
+        let (damped, next) = backtrack(|step| {let next = fx.eval(xn - step); (next, next.abs())}, full, root.abs());
+
+        xn -= damped;
+        root = next;
+
+        let iteration = Iteration{iteration: iterations + 1, x: xn, residual: root, step: damped, bracket: None};
+        if root.is_finite() && !observer.observe(&iteration) && !options.f_converged(root) {
+            return Err(RootError::Stopped);
+        }
+    }
+
+    Err(RootError::MaxIterations)
+}
+
+// the newton correction numerator/denominator, with the checks every newton-like method needs
+fn newton_step(numerator: f64, denominator: f64) -> Result<f64, RootError> {
+    if denominator == 0.0 {
+        Err(RootError::ZeroDerivative)
+    } else if !numerator.is_finite() || !denominator.is_finite() {
+        Err(RootError::NonFinite)
+    } else {
+        Ok(numerator/denominator)
+    }
+}
+
+// calculates a root of a certain function starting at a certain point, like newton_root, but also
+// works out its multiplicity and gives it back with the root. Plain newton only converges linearly
+// on a root of multiplicity m, each correction f/f' is (m-1)/m times the one before, so that ratio
+// tells m. Once two estimates in a row agree the solver switches to the modified newton iteration
+// x - m*f/f', which converges quadratically again. Returns None if it fails
+pub fn modified_newton_root<F, O>(fx: F, xo: f64, options: O) -> Option<(f64, usize)>
+where
+    F: FnMut(f64) -> f64,
+    O: Into<SolverOptions>,
+{
+    try_modified_newton_root(fx, xo, options).ok().map(|(r, m)| (r.root, m))
+}
+
+// same as modified_newton_root, but reports how it went, and why it failed if it did. Close to a
+// multiple root f (and its estimated derivative even more) is mostly rounding noise, and the best
+// we can do is around eps^(1/m)*max(|x|, 1) away from it. So a root is given back when |f| gets
+// below f_abs_tol, when the step gets below the x tolerances, or, once the multiplicity m is above
+// 1, when the step gets below eps^(1/m)*max(|x|, 1). If neither the modified step nor a
+// (backtracked) plain one makes |f| any smaller before that, the noise of f is worse than the
+// solver can tell apart from a root, and it gives Stalled. The multiplicity keeps being estimated
+// after the switch, since far from a root plain newton can also halve its steps and make a bad
+// first guess (x^2 - 4 looks a lot like a double root at 0 from x = 100). A cluster of close roots
+// looks like a single root of their total multiplicity from far away, the solver ends up on one of
+// them and gives the multiplicity of that one alone, the others have to be looked for from other
+// starting points
+pub fn try_modified_newton_root<F, O>(fx: F, xo: f64, options: O) -> Result<(RootReport, usize), RootError>
+where
+    F: FnMut(f64) -> f64,
+    O: Into<SolverOptions>,
+{
+    try_modified_newton_root_observed(fx, xo, options, &mut |_: &Iteration| true)
+}
+
+// same as try_modified_newton_root, with an observer like try_newton_root_observed
+pub fn try_modified_newton_root_observed<F, O, Ob>(fx: F, xo: f64, options: O, observer: &mut Ob) -> Result<(RootReport, usize), RootError>
+where
+    F: FnMut(f64) -> f64,
+    O: Into<SolverOptions>,
+    Ob: Observer + ?Sized,
+{
+    let options = options.into();
+    let mut fx = Counted::new(fx, options.max_evaluations);
+
     let mut xn = xo;
-    let mut root = fx(xn);
+    let mut root = fx.eval(xn);
+
+    let mut multiplicity = 1;
+    let mut estimate = 1;
+    let mut previous: Option<f64> = None;
+
+    for iterations in 0..=options.max_iterations {
+        if !xn.is_finite() || !root.is_finite() {
+            return Err(RootError::NonFinite);
+        }
+
+        if options.f_converged(root) {
+            return Ok((fx.report(xn, root, iterations), multiplicity));
+        }
+
+        if iterations == options.max_iterations {
+            break;
+        }
+        fx.budget()?;
+
+        let correction = newton_step(root, calculate_derivative(|x| fx.eval(x), xn))?;
+
+        // a step of m times the correction from x leaves x - r = (M - m)*correction, where M is the
+        // real multiplicity, so the next correction is (M - m)/M times this one
+        if let Some(previous) = previous {
+            let ratio = correction/previous;
+            let guess = if ratio < 1.0 { (multiplicity as f64/(1.0 - ratio)).round().max(1.0) as usize } else { 1 };
+
+            if guess == estimate {
+                multiplicity = guess;
+            }
+            estimate = guess;
+        }
+        previous = Some(correction);
+
+        let mut step = multiplicity as f64*correction;
+        let mut next = fx.eval(xn - step);
+
+        if multiplicity > 1 && next.abs() >= root.abs() {
+            // either the multiplicity is wrong and a (backtracked) plain step does better, or we
+            // are down to the rounding noise and there is nothing left to gain
+            (step, next) = backtrack(|step| {let next = fx.eval(xn - step); (next, next.abs())}, correction, root.abs());
+
+            if !next.is_finite() || next.abs() >= root.abs() {
+                return Err(RootError::Stalled);
+            }
+
+            multiplicity = 1;
+            estimate = 1;
+            previous = None;
+        }
+
+        xn -= step;
+        root = next;
+
+        // the modified steps converge quadratically, so once they are down to the eps^(1/m) the
+        // root can be located to, the one just taken got us as close as we are going to get
+        let limit = f64::EPSILON.powf(1.0/multiplicity as f64)*xn.abs().max(1.0);
+
+        let converged = options.x_converged(xn, step) || (multiplicity > 1 && step.abs() <= limit);
+
+        if !root.is_finite() {
+            return Err(RootError::NonFinite);
+        }
+
+        let go_on = observer.observe(&Iteration{iteration: iterations + 1, x: xn, residual: root, step, bracket: None});
+
+        if converged {
+            return Ok((fx.report(xn, root, iterations + 1), multiplicity));
+        }
+        if !go_on && !options.f_converged(root) {
+            return Err(RootError::Stopped);
+        }
+    }
+
+    Err(RootError::MaxIterations)
+}
+
+// calculates the many roots of a certain function between xmin and xmax with the given options.
+// The domain is split in num_intervals equal subintervals and every one of them with a sign change
+// is solved with bisection, so the number of intervals you choose may impact the result: two roots
+// in the same subinterval cancel out and are missed, so it is usually a good idea to have a lot of
+// intervals. Roots closer than 1e-10 are merged, and tangent roots (like the one of (x-2)^2) are
+// not looked for, use bracket_root_many with a ScanOptions to change that. For polynomials use
+// bissec_root_many_polynomial, which can't miss any of them.
+// The function is shared between the threads, so it has to be Fn + Send + Sync
+pub fn bissec_root_many<F, O>(fx: F, xmin: f64, xmax: f64, num_intervals: i32, options: O) -> Vec<f64>
+where
+    F: Fn(f64) -> f64 + Send + Sync,
+    O: Into<SolverOptions>,
+{
+    bracket_root_many(fx, xmin, xmax, num_intervals, BracketMethod::Bisection, options)
+}
+
+// the guaranteed complete version of bissec_root_many for a polynomial: instead of a regular scan
+// the distinct real roots in (xmin, xmax] are isolated with sturm sequences, so there is no
+// num_intervals to get right, and each one is refined with bissec_root. Tangent roots (the ones of
+// even multiplicity) are found too. It is the same as Polynomial::real_roots
+pub fn bissec_root_many_polynomial<O>(p: &Polynomial, xmin: f64, xmax: f64, options: O) -> Vec<f64>
+where
+    O: Into<SolverOptions>,
+{
+    p.real_roots(xmin, xmax, options)
+}
+
+// how bracket_root_many scans its domain: the solver used in every subinterval, how close two
+// roots have to be to be merged into one, and whether to also look for tangent roots, the ones
+// where the function touches zero without changing sign. Those are found as the extrema of f (the
+// zeros of its derivative) where |f| drops below tangent_tol. The subintervals are shared by a
+// pool of worker threads, as many as threads says: None uses as many as the machine has and Some(1)
+// runs everything on the calling thread, which is handy for debugging. A BracketMethod converts
+// into the default scan with that method
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScanOptions {
+    pub method: BracketMethod,
+    pub merge_tol: f64,
+    pub tangent_roots: bool,
+    pub tangent_tol: f64,
+    pub threads: Option<usize>,
+}
+
+impl Default for ScanOptions {
+    fn default() -> ScanOptions {
+        ScanOptions{
+            method: BracketMethod::default(),
+            merge_tol: 1.0e-10,
+            tangent_roots: false,
+            tangent_tol: 1.0e-10,
+            threads: None,
+        }
+    }
+}
+
+impl From<BracketMethod> for ScanOptions {
+    fn from(method: BracketMethod) -> ScanOptions {
+        ScanOptions{method, ..Default::default()}
+    }
+}
+
+// same as bissec_root_many, but the subintervals are scanned as told by the ScanOptions (or just
+// with the given BracketMethod). The roots come back sorted
+pub fn bracket_root_many<F, S, O>(fx: F, xmin: f64, xmax: f64, num_intervals: i32, scan: S, options: O) -> Vec<f64>
+where
+    F: Fn(f64) -> f64 + Send + Sync,
+    S: Into<ScanOptions>,
+    O: Into<SolverOptions>,
+{
+    let scan = scan.into();
+    let options = options.into();
+    let num_intervals = num_intervals.max(0) as usize;
+    let delx = (xmax - xmin)/num_intervals as f64;
+
+    let threads = scan.threads
+        .unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()))
+        .clamp(1, num_intervals.max(1));
+
+    // the workers grab chunks of subintervals from this counter until there are none left, small
+    // enough that a slow region of the domain doesn't keep a single thread busy while the others
+    // are done
+    let next_chunk = AtomicUsize::new(0);
+    let chunk_size = (num_intervals/(8*threads)).max(1);
+
+    let worker = || {
+        let mut roots = Vec::new();
+
+        loop {
+            let start = next_chunk.fetch_add(chunk_size, Ordering::Relaxed);
+            if start >= num_intervals {
+                break;
+            }
+
+            for i in start..(start + chunk_size).min(num_intervals) {
+                let xmin_temp = xmin + (i as f64 * delx);
+                let xmax_temp = xmin + ((i+1) as f64 * delx);
+
+                if let Some(v) = scan_interval(&fx, xmin_temp, xmax_temp, scan, options) {
+                    roots.push(v);
+                }
+            }
+        }
+
+        roots
+    };
+
+    let mut result = if threads == 1 {
+        worker()
+    } else {
+        thread::scope(|s| {
+            let handles: Vec<_> = (0..threads).map(|_| s.spawn(worker)).collect();
+            handles.into_iter().flat_map(|handle| handle.join().unwrap()).collect()
+        })
+    };
+
+    merge_roots(&mut result, scan.merge_tol);
+    result
+}
+
+// looks for a root in a single subinterval of the scan, a sign change goes to the bracketing
+// solver and otherwise, if asked to, it looks for a tangent root: the derivative has to point
+// towards zero on the left end and away from it on the right one, so |f| has a minimum inside
+fn scan_interval<F>(fx: &F, xmin: f64, xmax: f64, scan: ScanOptions, options: SolverOptions) -> Option<f64>
+where
+    F: Fn(f64) -> f64,
+{
+    match scan.method.solve(fx, xmin, xmax, options) {
+        Ok(report) => return Some(report.root),
+        Err(RootError::NoSignChange) if scan.tangent_roots => {},
+        Err(_) => return None,
+    }
+
+    let sign = fx(xmin).signum();
+    let dmin = sign*calculate_derivative(fx, xmin);
+    let dmax = sign*calculate_derivative(fx, xmax);
+
+    if !(dmin < 0.0 && dmax >= 0.0) {
+        return None;
+    }
+
+    let root = scan.method.solve(|x| calculate_derivative(fx, x), xmin, xmax, options).ok()?.root;
+    if fx(root).abs() <= scan.tangent_tol {
+        Some(root)
+    } else {
+        None
+    }
+}
+
+// sorts the roots and merges the ones closer than merge_tol, keeping the first of each group
+fn merge_roots(roots: &mut Vec<f64>, merge_tol: f64) {
+    roots.sort_by(|a, b| a.total_cmp(b));
+
+    let mut last = f64::NEG_INFINITY;
+    roots.retain(|&root| {
+        if root - last <= merge_tol {
+            return false;
+        }
+
+        last = root;
+        true
+    });
+}
+
+// the solvers that take a bracket [xmin, xmax] with a sign change, so the ones that scan an
+// interval for many roots can be told which one to use
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BracketMethod {
+    #[default]
+    Bisection,
+    Brent,
+    Illinois,
+    Ridders,
+}
+
+impl BracketMethod {
+    pub fn solve<F, O>(self, fx: F, xmin: f64, xmax: f64, options: O) -> Result<RootReport, RootError>
+    where
+        F: FnMut(f64) -> f64,
+        O: Into<SolverOptions>,
+    {
+        match self {
+            BracketMethod::Bisection => try_bissec_root(fx, xmin, xmax, options),
+            BracketMethod::Brent => try_brent_root(fx, xmin, xmax, options),
+            BracketMethod::Illinois => try_regula_falsi_root(fx, xmin, xmax, options),
+            BracketMethod::Ridders => try_ridders_root(fx, xmin, xmax, options),
+        }
+    }
+}
+
+// calculates the root of a certain function between xmin and xmax, which must have a sign change
+// between them, otherwise it returns None (as it also does if the solver fails)
+pub fn bissec_root<F, O>(fx: F, xmin: f64, xmax: f64, options: O) -> Option<f64>
+where
+    F: FnMut(f64) -> f64,
+    O: Into<SolverOptions>,
+{
+    try_bissec_root(fx, xmin, xmax, options).ok().map(|r| r.root)
+}
+
+// same as bissec_root, but reports how it went, and why it failed if it did. When the interval
+// can't be split anymore (xmin and xmax are neighbouring floats) the midpoint is returned, since
+// there is no better answer to give
+pub fn try_bissec_root<F, O>(fx: F, xmin: f64, xmax: f64, options: O) -> Result<RootReport, RootError>
+where
+    F: FnMut(f64) -> f64,
+    O: Into<SolverOptions>,
+{
+    try_bissec_root_observed(fx, xmin, xmax, options, &mut |_: &Iteration| true)
+}
+
+// same as try_bissec_root, but the observer gets to see every iteration, with the bracket left
+// after it, and can stop the solver early
+pub fn try_bissec_root_observed<F, O, Ob>(fx: F, mut xmin: f64, mut xmax: f64, options: O, observer: &mut Ob) -> Result<RootReport, RootError>
+where
+    F: FnMut(f64) -> f64,
+    O: Into<SolverOptions>,
+    Ob: Observer + ?Sized,
+{
+    let options = options.into();
+    let mut fx = Counted::new(fx, options.max_evaluations);
+
+    let (mut fmin, _) = match eval_bracket(&mut fx, xmin, xmax) {
+        Ok(values) => values,
+        Err(early) => return early,
+    };
+
+    for iterations in 1..=options.max_iterations {
+        fx.budget()?;
+
+        let xmed = (xmin + xmax)/2.0;
+        let fmed = fx.eval(xmed);
+
+        if !fmed.is_finite() {
+            return Err(RootError::NonFinite);
+        }
+
+        let step = (xmax - xmin)/2.0;
+        let converged = options.f_converged(fmed) || options.x_converged(xmed, step);
+        let stuck = xmed == xmin || xmed == xmax;
+
+        if fmed.signum() == fmin.signum() {
+            xmin = xmed;
+            fmin = fmed;
+        } else {
+            xmax = xmed;
+        }
+
+        let iteration = Iteration{iteration: iterations, x: xmed, residual: fmed, step, bracket: Some((xmin, xmax))};
+        let go_on = observer.observe(&iteration);
+
+        if converged || stuck {
+            return Ok(fx.report(xmed, fmed, iterations));
+        }
+        if !go_on {
+            return Err(RootError::Stopped);
+        }
+    }
+
+    Err(RootError::MaxIterations)
+}
+
+// newton's method one step at a time, as an iterator over the iterations, so the caller decides
+// when to stop, for example with take_while on the step or the residual. It takes the same steps as
+// newton_root, with the same backtracking, and ends when it can't go on: the derivative vanishes,
+// something goes infinite or NaN, the root is hit exactly or the steps get down to the rounding of x
+pub fn newton_steps<F>(fx: F, xo: f64) -> NewtonSteps<F>
+where
+    F: FnMut(f64) -> f64,
+{
+    NewtonSteps{fx, x: xo, residual: None, iteration: 0, done: false}
+}
+
+// the residual is only known once the first next() gets it
+pub struct NewtonSteps<F> {
+    fx: F,
+    x: f64,
+    residual: Option<f64>,
+    iteration: usize,
+    done: bool,
+}
+
+impl<F> Iterator for NewtonSteps<F>
+where
+    F: FnMut(f64) -> f64,
+{
+    type Item = Iteration;
+
+    fn next(&mut self) -> Option<Iteration> {
+        if self.done {
+            return None;
+        }
+
+        let residual = match self.residual {
+            Some(residual) => residual,
+            None => *self.residual.insert((self.fx)(self.x)),
+        };
+
+        if residual == 0.0 || !self.x.is_finite() || !residual.is_finite() {
+            self.done = true;
+            return None;
+        }
+
+        let full = match newton_step(residual, calculate_derivative(&mut self.fx, self.x)) {
+            Ok(full) => full,
+            Err(_) => {
+                self.done = true;
+                return None;
+            },
+        };
+
+        let x = self.x;
+        let (damped, next) = backtrack(|step| {let next = (self.fx)(x - step); (next, next.abs())}, full, residual.abs());
+
+        self.x -= damped;
+        self.residual = Some(next);
+        self.iteration += 1;
+
+        // once the steps are down to the rounding of x it is just going back and forth between
+        // neighbouring floats
+        self.done = damped.abs() <= 2.0*f64::EPSILON*self.x.abs();
+
+        Some(Iteration{iteration: self.iteration, x: self.x, residual: next, step: damped, bracket: None})
+    }
+}
+
+// bisection one step at a time, as an iterator over the iterations, each one with the bracket left
+// after it. Fails right away like try_bissec_root if there is no sign change between xmin and xmax,
+// and the iterator ends when the bracket can't be split anymore or the root is hit exactly
+pub fn bissec_steps<F>(mut fx: F, xmin: f64, xmax: f64) -> Result<BissecSteps<F>, RootError>
+where
+    F: FnMut(f64) -> f64,
+{
+    let fmin = fx(xmin);
+    let fmax = fx(xmax);
+
+    if !fmin.is_finite() || !fmax.is_finite() {
+        return Err(RootError::NonFinite);
+    } else if fmin != 0.0 && fmax != 0.0 && fmin.signum() == fmax.signum() {
+        return Err(RootError::NoSignChange);
+    }
+
+    // a root on one of the ends leaves nothing to bisect, the bracket is collapsed on it
+    let (xmin, xmax) = if fmin == 0.0 { (xmin, xmin) } else if fmax == 0.0 { (xmax, xmax) } else { (xmin, xmax) };
+
+    Ok(BissecSteps{fx, xmin, xmax, fmin, iteration: 0, done: false})
+}
+
+pub struct BissecSteps<F> {
+    fx: F,
+    xmin: f64,
+    xmax: f64,
+    fmin: f64,
+    iteration: usize,
+    done: bool,
+}
+
+impl<F> Iterator for BissecSteps<F>
+where
+    F: FnMut(f64) -> f64,
+{
+    type Item = Iteration;
+
+    fn next(&mut self) -> Option<Iteration> {
+        if self.done {
+            return None;
+        }
+
+        let xmed = (self.xmin + self.xmax)/2.0;
+        let fmed = (self.fx)(xmed);
+
+        if !fmed.is_finite() {
+            self.done = true;
+            return None;
+        }
+
+        let step = (self.xmax - self.xmin)/2.0;
+        self.done = fmed == 0.0 || xmed == self.xmin || xmed == self.xmax;
+
+        if fmed.signum() == self.fmin.signum() {
+            self.xmin = xmed;
+            self.fmin = fmed;
+        } else {
+            self.xmax = xmed;
+        }
+        self.iteration += 1;
+
+        Some(Iteration{iteration: self.iteration, x: xmed, residual: fmed, step, bracket: Some((self.xmin, self.xmax))})
+    }
+}
+
+// calculates the root of a certain function between xmin and xmax with brent's method, it takes
+// the same bracket as bissec_root but mixes inverse quadratic interpolation and secant steps with
+// bisection, so it converges superlinearly while never leaving the bracket
+pub fn brent_root<F, O>(fx: F, xmin: f64, xmax: f64, options: O) -> Option<f64>
+where
+    F: FnMut(f64) -> f64,
+    O: Into<SolverOptions>,
+{
+    try_brent_root(fx, xmin, xmax, options).ok().map(|r| r.root)
+}
+
+// same as brent_root, but reports how it went, and why it failed if it did. This follows the
+// classic zbrent: b is always the best estimate, a the previous one and c the other end of the
+// bracket, interpolation is only accepted when it falls well inside the bracket and shrinks it
+// faster than bisection would
+pub fn try_brent_root<F, O>(fx: F, xmin: f64, xmax: f64, options: O) -> Result<RootReport, RootError>
+where
+    F: FnMut(f64) -> f64,
+    O: Into<SolverOptions>,
+{
+    try_brent_root_observed(fx, xmin, xmax, options, &mut |_: &Iteration| true)
+}
+
+// same as try_brent_root, with an observer like try_bissec_root_observed. Each iteration is the best
+// estimate b, with the last step taken
+pub fn try_brent_root_observed<F, O, Ob>(fx: F, xmin: f64, xmax: f64, options: O, observer: &mut Ob) -> Result<RootReport, RootError>
+where
+    F: FnMut(f64) -> f64,
+    O: Into<SolverOptions>,
+    Ob: Observer + ?Sized,
+{
+    let options = options.into();
+    let mut fx = Counted::new(fx, options.max_evaluations);
+
+    let (mut a, mut b) = (xmin, xmax);
+    let (mut fa, mut fb) = match eval_bracket(&mut fx, a, b) {
+        Ok(values) => values,
+        Err(early) => return early,
+    };
+
+    let (mut c, mut fc) = (b, fb);
+    let mut d = b - a;
+    let mut e = d;
+    let mut step = 0.0;
+
+    for iterations in 1..=options.max_iterations {
+        if fb.signum() == fc.signum() {
+            c = a;
+            fc = fa;
+            d = b - a;
+            e = d;
+        }
+
+        if fc.abs() < fb.abs() {
+            a = b;
+            b = c;
+            c = a;
+            fa = fb;
+            fb = fc;
+            fc = fa;
+        }
+
+        let tol = 2.0*f64::EPSILON*b.abs() + 0.5*(options.x_abs_tol + options.x_rel_tol*b.abs());
+        let xm = 0.5*(c - b);
+
+        let converged = xm.abs() <= tol || options.f_converged(fb);
+
+        // the observer sees b once it has been sorted out which end is the best
+        let go_on = iterations == 1 || observer.observe(&Iteration{iteration: iterations - 1, x: b, residual: fb, step, bracket: Some((b.min(c), b.max(c)))});
+
+        if converged {
+            return Ok(fx.report(b, fb, iterations - 1));
+        }
+        if !go_on {
+            return Err(RootError::Stopped);
+        }
+        fx.budget()?;
+
+        if e.abs() >= tol && fa.abs() > fb.abs() {
+            let s = fb/fa;
+            let (mut p, mut q);
+
+            if a == c {
+                // only two points, secant step
+                p = 2.0*xm*s;
+                q = 1.0 - s;
+            } else {
+                // inverse quadratic interpolation
+                let r = fb/fc;
+                q = fa/fc;
+                p = s*(2.0*xm*q*(q - r) - (b - a)*(r - 1.0));
+                q = (q - 1.0)*(r - 1.0)*(s - 1.0);
+            }
+
+            if p > 0.0 {
+                q = -q;
+            }
+            p = p.abs();
+
+            let min1 = 3.0*xm*q - (tol*q).abs();
+            let min2 = (e*q).abs();
+
+            if 2.0*p < min1.min(min2) {
+                e = d;
+                d = p/q;
+            } else {
+                d = xm;
+                e = d;
+            }
+        } else {
+            d = xm;
+            e = d;
+        }
+
+        a = b;
+        fa = fb;
+
+        if d.abs() > tol {
+            b += d;
+        } else {
+            b += tol.copysign(xm);
+        }
+
+        fb = fx.eval(b);
+        if !fb.is_finite() {
+            return Err(RootError::NonFinite);
+        }
+        step = b - a;
+    }
+
+    Err(RootError::MaxIterations)
+}
+
+// calculates the root of a certain function with the secant method, starting from the two points
+// x0 and x1. It is newton with the derivative replaced by the slope through the last two iterates,
+// so it never calls calculate_derivative, which is nice for noisy functions. Like newton_root it
+// returns NaN if it fails
+pub fn secant_root<F, O>(fx: F, x0: f64, x1: f64, options: O) -> f64
+where
+    F: FnMut(f64) -> f64,
+    O: Into<SolverOptions>,
+{
+    try_secant_root(fx, x0, x1, options).map(|r| r.root).unwrap_or(f64::NAN)
+}
+
+// same as secant_root, but reports how it went, and why it failed if it did. A flat secant (both
+// points with the same value) is reported as ZeroDerivative
+pub fn try_secant_root<F, O>(fx: F, x0: f64, x1: f64, options: O) -> Result<RootReport, RootError>
+where
+    F: FnMut(f64) -> f64,
+    O: Into<SolverOptions>,
+{
+    try_secant_root_observed(fx, x0, x1, options, &mut |_: &Iteration| true)
+}
+
+// same as try_secant_root, with an observer like try_newton_root_observed
+pub fn try_secant_root_observed<F, O, Ob>(fx: F, x0: f64, x1: f64, options: O, observer: &mut Ob) -> Result<RootReport, RootError>
+where
+    F: FnMut(f64) -> f64,
+    O: Into<SolverOptions>,
+    Ob: Observer + ?Sized,
+{
+    let options = options.into();
+    let mut fx = Counted::new(fx, options.max_evaluations);
+
+    let (mut xa, mut xb) = (x0, x1);
+    let (mut fa, mut fb) = (fx.eval(xa), fx.eval(xb));
+
+    for iterations in 0..=options.max_iterations {
+        if !xb.is_finite() || !fa.is_finite() || !fb.is_finite() {
+            return Err(RootError::NonFinite);
+        }
+
+        if options.f_converged(fb) {
+            return Ok(fx.report(xb, fb, iterations));
+        }
+
+        if iterations == options.max_iterations {
+            break;
+        }
+        fx.budget()?;
+
+        if fb == fa {
+            return Err(RootError::ZeroDerivative);
+        }
+
+        let step = fb*(xb - xa)/(fb - fa);
+        xa = xb;
+        fa = fb;
+        xb -= step;
+        fb = fx.eval(xb);
+
+        if !fb.is_finite() {
+            continue;
+        }
+
+        let iteration = Iteration{iteration: iterations + 1, x: xb, residual: fb, step, bracket: None};
+        let go_on = observer.observe(&iteration);
+
+        if options.x_converged(xb, step) || options.f_converged(fb) {
+            return Ok(fx.report(xb, fb, iterations + 1));
+        }
+        if !go_on {
+            return Err(RootError::Stopped);
+        }
+    }
+
+    Err(RootError::MaxIterations)
+}
+
+// calculates the root of a certain function between xmin and xmax with the illinois version of
+// regula falsi. Plain false position keeps one end of the bracket stuck forever on convex
+// functions, so whenever the same end is kept twice in a row its value is halved, which pulls the
+// next interpolation towards it
+pub fn regula_falsi_root<F, O>(fx: F, xmin: f64, xmax: f64, options: O) -> Option<f64>
+where
+    F: FnMut(f64) -> f64,
+    O: Into<SolverOptions>,
+{
+    try_regula_falsi_root(fx, xmin, xmax, options).ok().map(|r| r.root)
+}
+
+// same as regula_falsi_root, but reports how it went, and why it failed if it did
+pub fn try_regula_falsi_root<F, O>(fx: F, xmin: f64, xmax: f64, options: O) -> Result<RootReport, RootError>
+where
+    F: FnMut(f64) -> f64,
+    O: Into<SolverOptions>,
+{
+    try_regula_falsi_root_observed(fx, xmin, xmax, options, &mut |_: &Iteration| true)
+}
+
+// same as try_regula_falsi_root, with an observer like try_bissec_root_observed
+pub fn try_regula_falsi_root_observed<F, O, Ob>(fx: F, xmin: f64, xmax: f64, options: O, observer: &mut Ob) -> Result<RootReport, RootError>
+where
+    F: FnMut(f64) -> f64,
+    O: Into<SolverOptions>,
+    Ob: Observer + ?Sized,
+{
+    let options = options.into();
+    let mut fx = Counted::new(fx, options.max_evaluations);
+
+    let (mut a, mut b) = (xmin, xmax);
+    let (mut fa, mut fb) = match eval_bracket(&mut fx, a, b) {
+        Ok(values) => values,
+        Err(early) => return early,
+    };
+
+    // which end was replaced in the last iteration, -1 for a and 1 for b
+    let mut side = 0;
+    let mut previous = a;
+
+    for iterations in 1..=options.max_iterations {
+        fx.budget()?;
+
+        let c = (a*fb - b*fa)/(fb - fa);
+        let fc = fx.eval(c);
+
+        if !fc.is_finite() {
+            return Err(RootError::NonFinite);
+        }
+
+        let stalled = c == a || c == b || options.x_converged(c, c - previous);
+        let converged = options.f_converged(fc) || stalled;
+
+        if fc.signum() == fb.signum() {
+            b = c;
+            fb = fc;
+            if side == 1 {
+                fa /= 2.0;
+            }
+            side = 1;
+        } else {
+            a = c;
+            fa = fc;
+            if side == -1 {
+                fb /= 2.0;
+            }
+            side = -1;
+        }
+
+        let iteration = Iteration{iteration: iterations, x: c, residual: fc, step: c - previous, bracket: Some((a.min(b), a.max(b)))};
+        let go_on = observer.observe(&iteration);
+
+        if converged || options.x_converged(c, (b - a)/2.0) {
+            return Ok(fx.report(c, fc, iterations));
+        }
+        if !go_on {
+            return Err(RootError::Stopped);
+        }
+        previous = c;
+    }
+
+    Err(RootError::MaxIterations)
+}
+
+// calculates the root of a certain function between xmin and xmax with ridders' method. Each
+// iteration evaluates the midpoint and fits an exponential through the three points, which gives
+// quadratic convergence for the price of two function calls, and the new point never leaves the
+// bracket
+pub fn ridders_root<F, O>(fx: F, xmin: f64, xmax: f64, options: O) -> Option<f64>
+where
+    F: FnMut(f64) -> f64,
+    O: Into<SolverOptions>,
+{
+    try_ridders_root(fx, xmin, xmax, options).ok().map(|r| r.root)
+}
+
+// same as ridders_root, but reports how it went, and why it failed if it did
+pub fn try_ridders_root<F, O>(fx: F, xmin: f64, xmax: f64, options: O) -> Result<RootReport, RootError>
+where
+    F: FnMut(f64) -> f64,
+    O: Into<SolverOptions>,
+{
+    try_ridders_root_observed(fx, xmin, xmax, options, &mut |_: &Iteration| true)
+}
+
+// same as try_ridders_root, with an observer like try_bissec_root_observed. The step of each
+// iteration is the one from the midpoint of the bracket to the new point
+pub fn try_ridders_root_observed<F, O, Ob>(fx: F, xmin: f64, xmax: f64, options: O, observer: &mut Ob) -> Result<RootReport, RootError>
+where
+    F: FnMut(f64) -> f64,
+    O: Into<SolverOptions>,
+    Ob: Observer + ?Sized,
+{
+    let options = options.into();
+    let mut fx = Counted::new(fx, options.max_evaluations);
+
+    let (mut a, mut b) = (xmin, xmax);
+    let (mut fa, mut fb) = match eval_bracket(&mut fx, a, b) {
+        Ok(values) => values,
+        Err(early) => return early,
+    };
+
+    let mut previous = f64::NAN;
+
+    for iterations in 1..=options.max_iterations {
+        fx.budget()?;
+
+        let xm = (a + b)/2.0;
+        let fm = fx.eval(xm);
+        if !fm.is_finite() {
+            return Err(RootError::NonFinite);
+        }
+
+        let s = (fm*fm - fa*fb).sqrt();
+        if fm == 0.0 || s == 0.0 {
+            observer.observe(&Iteration{iteration: iterations, x: xm, residual: fm, step: 0.0, bracket: Some((a.min(b), a.max(b)))});
+            return Ok(fx.report(xm, fm, iterations));
+        }
+
+        let xnew = xm + (xm - a)*(fa - fb).signum()*fm/s;
+        let fnew = fx.eval(xnew);
+        if !fnew.is_finite() {
+            return Err(RootError::NonFinite);
+        }
+
+        let converged = options.f_converged(fnew) || options.x_converged(xnew, xnew - previous);
+        previous = xnew;
+
+        if fm.signum() != fnew.signum() {
+            a = xm;
+            fa = fm;
+            b = xnew;
+            fb = fnew;
+        } else if fa.signum() != fnew.signum() {
+            b = xnew;
+            fb = fnew;
+        } else {
+            a = xnew;
+            fa = fnew;
+        }
+
+        let iteration = Iteration{iteration: iterations, x: xnew, residual: fnew, step: xnew - xm, bracket: Some((a.min(b), a.max(b)))};
+        let go_on = observer.observe(&iteration);
+
+        if converged || options.x_converged(xnew, (b - a)/2.0) {
+            return Ok(fx.report(xnew, fnew, iterations));
+        }
+        if !go_on {
+            return Err(RootError::Stopped);
+        }
+    }
+
+    Err(RootError::MaxIterations)
+}
+
+// how find_bracket looks for a sign change. The first points are step*max(1, |xo|) away from xo,
+// and every next one is growth times further, until they are max_distance away from xo or
+// max_evaluations calls to the function were spent
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BracketSearch {
+    pub step: f64,
+    pub growth: f64,
+    pub max_distance: f64,
+    pub max_evaluations: usize,
+}
+
+impl Default for BracketSearch {
+    fn default() -> BracketSearch {
+        BracketSearch{
+            step: 1.0e-2,
+            growth: 2.0,
+            max_distance: 1.0e6,
+            max_evaluations: 100,
+        }
+    }
+}
+
+// finds an interval [xmin, xmax] around xo where the function changes sign, so it can be given
+// straight to bissec_root or any of the other bracketing solvers. It walks outwards from xo on
+// both sides with geometrically growing steps and returns the first pair of neighbouring points
+// with a sign change, so the bracket is as tight as the scan allows. A side where the function
+// stops being finite (like going below zero on a sqrt) is not explored any further. Returns None
+// when no sign change is found within the limits of the search
+pub fn find_bracket<F>(fx: F, xo: f64, search: Option<BracketSearch>) -> Option<(f64, f64)>
+where
+    F: FnMut(f64) -> f64,
+{
+    try_find_bracket(fx, xo, search).ok()
+}
+
+// same as find_bracket, but tells why it failed. NoSignChange means both sides were explored up
+// to max_distance (or hit the edge of the domain), MaxEvaluations that it ran out of calls first
+pub fn try_find_bracket<F>(fx: F, xo: f64, search: Option<BracketSearch>) -> Result<(f64, f64), RootError>
+where
+    F: FnMut(f64) -> f64,
+{
+    let search = search.unwrap_or_default();
+    let mut fx = Counted::new(fx, search.max_evaluations);
+
+    let fo = fx.eval(xo);
+    if !fo.is_finite() {
+        return Err(RootError::NonFinite);
+    } else if fo == 0.0 {
+        return Ok((xo, xo));
+    }
+
+    let mut delx = search.step*xo.abs().max(1.0);
+
+    // the last point reached on each side (left, right), None once that side is done
+    let mut left = Some((xo, fo));
+    let mut right = Some((xo, fo));
+
+    while left.is_some() || right.is_some() {
+        if delx > search.max_distance {
+            return Err(RootError::NoSignChange);
+        }
+
+        for (side, sign) in [(&mut left, -1.0), (&mut right, 1.0)] {
+            let Some((xlast, flast)) = *side else {
+                continue;
+            };
+
+            fx.budget()?;
+            let x = xo + sign*delx;
+            let f = fx.eval(x);
+
+            if !f.is_finite() {
+                *side = None;
+            } else if f == 0.0 || f.signum() != flast.signum() {
+                return Ok((xlast.min(x), xlast.max(x)));
+            } else {
+                *side = Some((x, f));
+            }
+        }
+
+        delx *= search.growth;
+    }
+
+    Err(RootError::NoSignChange)
+}
+
+// evaluates both ends of a bracket for the bracketing solvers. The Err side is what the solver
+// should return right away, either because the bracket is no good or because one of its ends is
+// already an exact root
+fn eval_bracket<F>(fx: &mut Counted<F>, xmin: f64, xmax: f64) -> Result<(f64, f64), Result<RootReport, RootError>>
+where
+    F: FnMut(f64) -> f64,
+{
+    let fmin = fx.eval(xmin);
+    let fmax = fx.eval(xmax);
+
+    if !fmin.is_finite() || !fmax.is_finite() {
+        Err(Err(RootError::NonFinite))
+    } else if fmin == 0.0 {
+        Err(Ok(fx.report(xmin, fmin, 0)))
+    } else if fmax == 0.0 {
+        Err(Ok(fx.report(xmax, fmax, 0)))
+    } else if fmin.signum() == fmax.signum() {
+        Err(Err(RootError::NoSignChange))
+    } else {
+        Ok((fmin, fmax))
+    }
+}
+
+// how fixed_point speeds up the iteration x = g(x). Plain just iterates, Aitken iterates the same
+// way but reports aitken's delta squared extrapolation of every three iterates, and Steffensen
+// restarts the iteration from that extrapolation each time, which converges quadratically and even
+// finds fixed points that repel the plain iteration. The extrapolation divides by a difference of
+// differences of the iterates, so with Aitken the estimates stop improving at around 1e-14 and a
+// tighter x tolerance than that will never be met
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FixedPointAcceleration {
+    Plain,
+    Aitken,
+    #[default]
+    Steffensen,
+}
+
+// the number of steps in a row that have to grow for fixed_point to decide it's diverging
+const DIVERGENCE_STREAK: usize = 5;
+
+// calculates the fixed point of a certain function, the x where gx(x) = x, starting at xo. This is
+// better than looking for the root of gx(x) - x when gx is a contraction, like in self consistent
+// field problems, since the iteration keeps that structure. Returns NaN if it fails
+pub fn fixed_point<G, O>(gx: G, xo: f64, acceleration: FixedPointAcceleration, options: O) -> f64
+where
+    G: FnMut(f64) -> f64,
+    O: Into<SolverOptions>,
+{
+    try_fixed_point(gx, xo, acceleration, options).map(|r| r.root).unwrap_or(f64::NAN)
+}
+
+// same as fixed_point, but reports how it went, and why it failed if it did. The residual in the
+// report is gx(x) - x, and the solver gives up with Diverged when the steps of the underlying
+// iteration keep growing
+pub fn try_fixed_point<G, O>(gx: G, xo: f64, acceleration: FixedPointAcceleration, options: O) -> Result<RootReport, RootError>
+where
+    G: FnMut(f64) -> f64,
+    O: Into<SolverOptions>,
+{
+    try_fixed_point_observed(gx, xo, acceleration, options, &mut |_: &Iteration| true)
+}
+
+// same as try_fixed_point, with an observer like try_newton_root_observed. Each iteration is the
+// new estimate and the step to it, but gx isn't called again just to tell the observer, so the
+// residual is gx(x) - x at the iterate the iteration started from, except on the last iteration,
+// where it is the one of the report
+pub fn try_fixed_point_observed<G, O, Ob>(gx: G, xo: f64, acceleration: FixedPointAcceleration, options: O, observer: &mut Ob) -> Result<RootReport, RootError>
+where
+    G: FnMut(f64) -> f64,
+    O: Into<SolverOptions>,
+    Ob: Observer + ?Sized,
+{
+    let options = options.into();
+    let mut gx = Counted::new(gx, options.max_evaluations);
+
+    // x is the iterate of the underlying sequence and estimate the one we report, they are the
+    // same except with aitken
+    let mut x = xo;
+    let mut estimate = xo;
+    let mut previous_step = f64::INFINITY;
+    let mut growing = 0;
+
+    for iterations in 0..options.max_iterations {
+        gx.budget()?;
+
+        // blowing up right after the steps started growing is just diverging faster than the
+        // streak can tell
+        let x1 = gx.eval(x);
+        if !x1.is_finite() {
+            return Err(if growing > 0 { RootError::Diverged } else { RootError::NonFinite });
+        }
+
+        if options.f_converged(x1 - x) {
+            return Ok(gx.report(x, x1 - x, iterations));
+        }
+
+        let (next, next_estimate) = match acceleration {
+            FixedPointAcceleration::Plain => (x1, x1),
+            FixedPointAcceleration::Aitken | FixedPointAcceleration::Steffensen => {
+                let x2 = gx.eval(x1);
+                let extrapolated = aitken(x, x1, x2).unwrap_or(x2);
+
+                if acceleration == FixedPointAcceleration::Aitken {
+                    (x2, extrapolated)
+                } else {
+                    (extrapolated, extrapolated)
+                }
+            },
+        };
+
+        if !next.is_finite() || !next_estimate.is_finite() {
+            return Err(if growing > 0 { RootError::Diverged } else { RootError::NonFinite });
+        }
+
+        let step = (next - x).abs();
+        if step > previous_step {
+            growing += 1;
+            if growing >= DIVERGENCE_STREAK {
+                return Err(RootError::Diverged);
+            }
+        } else {
+            growing = 0;
+        }
+        previous_step = step;
+
+        let delta = next_estimate - estimate;
+        let converged = options.x_converged(next_estimate, delta);
+        let mut residual = x1 - x;
+        x = next;
+        estimate = next_estimate;
+
+        if converged {
+            residual = gx.eval(estimate) - estimate;
+        }
+
+        let go_on = observer.observe(&Iteration{iteration: iterations + 1, x: estimate, residual, step: delta, bracket: None});
+
+        if converged {
+            return Ok(gx.report(estimate, residual, iterations + 1));
+        }
+        if !go_on {
+            // the estimate can still be an exact fixed point, which is only known with one more call
+            let residual = gx.eval(estimate) - estimate;
+            if options.f_converged(residual) {
+                return Ok(gx.report(estimate, residual, iterations + 1));
+            }
+            return Err(RootError::Stopped);
+        }
+    }
+
+    Err(RootError::MaxIterations)
+}
+
+// aitken's delta squared extrapolation of three consecutive iterates, None when they are on a
+// straight line and there is nothing to extrapolate
+fn aitken(x0: f64, x1: f64, x2: f64) -> Option<f64> {
+    let denominator = (x2 - x1) - (x1 - x0);
+    if denominator == 0.0 {
+        return None;
+    }
+
+    Some(x2 - (x2 - x1).powf(2.0)/denominator)
+}
+
+// calculates a root of a certain complex function starting at a certain point, the same way
+// newton_root does but in the complex plane, with calculate_complex_derivative for the derivative.
+// The function has to be analytic (no conj or abs in there) for the derivative to make sense, and
+// a real function only finds complex roots from a complex starting point, since the newton steps
+// never leave the real line otherwise. Returns Complex::NAN if it fails
+pub fn newton_root_complex<F, Z, O>(fx: F, zo: Z, options: O) -> Complex
+where
+    F: FnMut(Complex) -> Complex,
+    Z: Into<Complex>,
+    O: Into<SolverOptions>,
+{
+    try_newton_root_complex(fx, zo, options).map(|r| r.root).unwrap_or(Complex::NAN)
+}
+
+// same as newton_root_complex, but reports how it went, and why it failed if it did. The
+// tolerances of the options are applied to the modulus of the step and of the residual
+pub fn try_newton_root_complex<F, Z, O>(fx: F, zo: Z, options: O) -> Result<RootReport<Complex>, RootError>
+where
+    F: FnMut(Complex) -> Complex,
+    Z: Into<Complex>,
+    O: Into<SolverOptions>,
+{
+    let options = options.into();
+    let mut fx = Counted::new(fx, options.max_evaluations);
+
+    let mut zn = zo.into();
+    let mut root = fx.eval_complex(zn);
+
+    for iterations in 0..=options.max_iterations {
+        if !zn.is_finite() || !root.is_finite() {
+            return Err(RootError::NonFinite);
+        }
+
+        if options.f_converged(root.abs()) {
+            return Ok(fx.report_complex(zn, root, iterations));
+        }
+
+        if iterations == options.max_iterations {
+            break;
+        }
+        fx.budget()?;
+
+        let derivative = calculate_complex_derivative(|z| fx.eval_complex(z), zn);
+        if derivative == Complex::ZERO {
+            return Err(RootError::ZeroDerivative);
+        } else if !derivative.is_finite() {
+            return Err(RootError::NonFinite);
+        }
+
+        let full = root/derivative;
+        if options.x_converged(zn.abs(), full.abs()) {
+            zn -= full;
+            root = fx.eval_complex(zn);
+
+            if !root.is_finite() {
+                return Err(RootError::NonFinite);
+            }
+            return Ok(fx.report_complex(zn, root, iterations + 1));
+        }
+
+        let (damped, next) = backtrack(|step| {let next = fx.eval_complex(zn - step); (next, next.abs())}, full, root.abs());
+
+        zn -= damped;
+        root = next;
+    }
+
+    Err(RootError::MaxIterations)
+}
+
+// calculates a root of a certain complex function with muller's method, from three starting
+// points. It fits a parabola through the last three iterates and jumps to its closest root, which
+// is complex whenever the parabola doesn't cross zero, so unlike newton it finds complex roots even
+// from real starting points and with real functions. It doesn't need any derivative either.
+// Returns Complex::NAN if it fails
+pub fn muller_root<F, Z, O>(fx: F, z0: Z, z1: Z, z2: Z, options: O) -> Complex
+where
+    F: FnMut(Complex) -> Complex,
+    Z: Into<Complex>,
+    O: Into<SolverOptions>,
+{
+    try_muller_root(fx, z0, z1, z2, options).map(|r| r.root).unwrap_or(Complex::NAN)
+}
+
+// same as muller_root, but reports how it went, and why it failed if it did. A parabola that
+// can't be fitted (repeated points) or is flat is reported as ZeroDerivative
+pub fn try_muller_root<F, Z, O>(fx: F, z0: Z, z1: Z, z2: Z, options: O) -> Result<RootReport<Complex>, RootError>
+where
+    F: FnMut(Complex) -> Complex,
+    Z: Into<Complex>,
+    O: Into<SolverOptions>,
+{
+    let options = options.into();
+    let mut fx = Counted::new(fx, options.max_evaluations);
+
+    let (mut z0, mut z1, mut z2) = (z0.into(), z1.into(), z2.into());
+    let (mut f0, mut f1, mut f2) = (fx.eval_complex(z0), fx.eval_complex(z1), fx.eval_complex(z2));
+
+    for iterations in 0..=options.max_iterations {
+        if !f0.is_finite() || !f1.is_finite() || !f2.is_finite() || !z2.is_finite() {
+            return Err(RootError::NonFinite);
+        }
+
+        if options.f_converged(f2.abs()) {
+            return Ok(fx.report_complex(z2, f2, iterations));
+        }
+
+        if iterations == options.max_iterations {
+            break;
+        }
+        fx.budget()?;
+
+        let h1 = z1 - z0;
+        let h2 = z2 - z1;
+        if h1 == Complex::ZERO || h2 == Complex::ZERO || h1 + h2 == Complex::ZERO {
+            return Err(RootError::ZeroDerivative);
+        }
+
+        // the parabola a*(z - z2)^2 + b*(z - z2) + c through the three points
+        let d1 = (f1 - f0)/h1;
+        let d2 = (f2 - f1)/h2;
+        let a = (d2 - d1)/(h2 + h1);
+        let b = a*h2 + d2;
+        let c = f2;
+
+        // the root of the parabola closest to z2, taking the sign that avoids cancellation
+        let discriminant = (b*b - 4.0*a*c).sqrt();
+        let plus = b + discriminant;
+        let minus = b - discriminant;
+        let denominator = if plus.abs() >= minus.abs() { plus } else { minus };
+
+        if denominator == Complex::ZERO {
+            return Err(RootError::ZeroDerivative);
+        }
+
+        let step = -2.0*c/denominator;
+        let z3 = z2 + step;
+        let f3 = fx.eval_complex(z3);
+
+        (z0, z1, z2) = (z1, z2, z3);
+        (f0, f1, f2) = (f1, f2, f3);
+
+        if options.x_converged(z2.abs(), step.abs()) && f2.is_finite() {
+            return Ok(fx.report_complex(z2, f2, iterations + 1));
+        }
+    }
+
+    Err(RootError::MaxIterations)
+}
+
+// what interval_newton found in the box: every verified interval holds exactly one root of the
+// function, and every root in the box is either in one of those or in one of the unresolved
+// intervals, which are the places it couldn't decide (multiple roots, roots too close together to
+// be told apart, or it ran out of evaluations)
+#[derive(Debug, Clone, PartialEq)]
+pub struct RootEnclosures {
+    pub verified: Vec<Interval>,
+    pub unresolved: Vec<Interval>,
+}
+
+// finds rigorous enclosures of all the roots of a function in the box x with the interval newton
+// method. fx and dfx are the function and its derivative written with Interval arithmetic, so they
+// give intervals holding every value the function and its derivative take on the interval they are
+// given. Parts of the box where fx doesn't hold zero have no roots and are dropped, parts where dfx
+// holds zero are bisected, and on the rest the newton operator N(X) = m - f(m)/f'(X), with m the
+// middle of X, holds every root in X. When N(X) falls inside X it proves there is exactly one root
+// in X, which is then narrowed down with X = N(X) ∩ X until the width stops getting smaller or the
+// x tolerances in the options are met. Where fx gives Interval::EMPTY (like sqrt of a negative box)
+// the function isn't defined, so there are no roots there either. Where it is EMPTY at m the
+// function isn't defined on the whole of X, the newton operator doesn't hold its roots, and
+// nothing it proved or cut off on the way there counts, so X is bisected as it was before any
+// newton step instead, and so is an X that would be verified while fx is EMPTY over it. The box can be unbounded, its infinite
+// ends are then cut off a growing piece at a time. Pieces of the box narrower than the x
+// tolerances that can't be decided end up unresolved, and so does whatever is left after
+// max_evaluations calls to fx
+pub fn interval_newton<F, D, O>(mut fx: F, mut dfx: D, x: Interval, options: O) -> RootEnclosures
+where
+    F: FnMut(Interval) -> Interval,
+    D: FnMut(Interval) -> Interval,
+    O: Into<SolverOptions>,
+{
+    let options = options.into();
+    let mut evaluations = 0;
+
+    let mut verified = Vec::new();
+    let mut unresolved = Vec::new();
+    let mut pending = vec![x];
+
+    // NaN bounds compare false both ways, so they count as holding zero, which is the safe side
+    let holds_zero = |x: Interval| !(x.lo > 0.0 || x.hi < 0.0);
+    let has_roots = |fx: Interval| !fx.is_empty() && holds_zero(fx);
+    let too_small = |x: Interval| options.x_converged(x.mid(), x.width());
+
+    'boxes: while let Some(mut x) = pending.pop() {
+        if evaluations >= options.max_evaluations {
+            unresolved.push(x);
+            continue;
+        }
+
+        evaluations += 1;
+        if !has_roots(fx(x)) {
+            continue;
+        }
+
+        let start = x;
+        let mut proven = false;
+
+        loop {
+            let derivative = dfx(x);
+
+            if holds_zero(derivative) {
+                match split_box(x) {
+                    Some(halves) if !too_small(x) => pending.extend(halves),
+                    _ => unresolved.push(x),
+                }
+                continue 'boxes;
+            }
+
+            let m = x.mid();
+            evaluations += 1;
+            let fm = fx(Interval::point(m));
+
+            let newton = Interval::point(m) - fm/derivative;
+            let next = newton.intersect(x);
+            proven = proven || newton.is_interior_of(x);
+
+            let done = proven && next.is_some_and(|next| next.width() >= x.width() || too_small(next) || evaluations >= options.max_evaluations);
+
+            // f has to be defined at m for the newton operator to hold the roots of X, and on the
+            // enclosure it verifies, or the proof was about the formula of f and not about f
+            let defined = !fm.is_empty() && match next {
+                Some(next) if done => {
+                    evaluations += 1;
+                    !fx(next).is_empty()
+                },
+                _ => true,
+            };
+
+            if !defined {
+                match split_box(start) {
+                    Some(halves) if !too_small(start) => pending.extend(halves),
+                    _ => unresolved.push(start),
+                }
+                continue 'boxes;
+            }
+
+            let Some(next) = next else {
+                continue 'boxes;
+            };
+
+            if done {
+                verified.push(next);
+                continue 'boxes;
+            } else if !proven && (next.width() > x.width()/2.0 || next.width().is_infinite() || evaluations >= options.max_evaluations) {
+                match split_box(next) {
+                    Some(halves) if !too_small(next) && evaluations < options.max_evaluations => pending.extend(halves),
+                    _ => unresolved.push(next),
+                }
+                continue 'boxes;
+            }
+
+            x = next;
+        }
+    }
+
+    verified.sort_by(|a, b| a.lo.total_cmp(&b.lo));
+    unresolved.sort_by(|a, b| a.lo.total_cmp(&b.lo));
+
+    // neighbouring pieces left from the bisections are joined back together
+    let mut merged: Vec<Interval> = Vec::with_capacity(unresolved.len());
+    for x in unresolved {
+        match merged.last_mut() {
+            Some(last) if x.lo <= last.hi => *last = last.hull(x),
+            _ => merged.push(x),
+        }
+    }
+
+    RootEnclosures{verified, unresolved: merged}
+}
+
+// where interval_newton splits its boxes, off the middle like in the sturm isolation, since a root
+// right on the edge of a box can never be proven to be inside it
+const INTERVAL_SPLIT: f64 = 0.4813;
+
+// the box split in two, the left piece last so it is the next one looked at. An infinite end is cut
+// off at a finite point a few times further out than the other end, so an unbounded box is searched
+// a growing piece at a time. None when there is no float left to split it at
+fn split_box(x: Interval) -> Option<[Interval; 2]> {
+    let split = match (x.lo.is_finite(), x.hi.is_finite()) {
+        (true, true) => x.lo + (x.hi - x.lo)*INTERVAL_SPLIT,
+        (true, false) => x.lo + x.lo.abs().max(1.0)/INTERVAL_SPLIT,
+        (false, true) => x.hi - x.hi.abs().max(1.0)/INTERVAL_SPLIT,
+        (false, false) => -INTERVAL_SPLIT,
+    };
+    let split = if split.is_finite() { split } else { x.mid() };
+
+    if x.lo < split && split < x.hi {
+        Some([Interval{lo: split, hi: x.hi}, Interval{lo: x.lo, hi: split}])
+    } else {
+        None
+    }
+}
+
+// solves the system of equations fx(x) = 0, where fx goes from R^n to R^n, with newton's method
+// starting at xo. The jacobian is estimated with calculate_jacobian, which takes 2n calls to fx,
+// so you may want to raise max_evaluations for big systems, or give it analytically with
+// newton_system_with_jacobian. The step is damped by a line search on |fx|^2, so it also gets
+// there from starting points where plain newton would diverge. Returns None if it fails
+pub fn newton_system<F, O>(fx: F, xo: &[f64], options: O) -> Option<Vec<f64>>
+where
+    F: FnMut(&[f64]) -> Vec<f64>,
+    O: Into<SolverOptions>,
+{
+    try_newton_system(fx, xo, options).ok().map(|r| r.root)
+}
+
+// same as newton_system, but reports how it went, and why it failed if it did. A singular
+// jacobian is reported as ZeroDerivative, and a system with more or fewer equations than unknowns
+// as NotSquare
+pub fn try_newton_system<F, O>(fx: F, xo: &[f64], options: O) -> Result<SystemReport, RootError>
+where
+    F: FnMut(&[f64]) -> Vec<f64>,
+    O: Into<SolverOptions>,
+{
+    let options = options.into();
+    let mut fx = Counted::new(fx, options.max_evaluations);
+
+    newton_system_iterate(&mut fx, xo, options, |fx, x| calculate_jacobian(|x| fx.eval_system(x), x))
+}
+
+// same as newton_system, but with the given jacobian, row i holding the derivatives of the ith
+// component of fx
+pub fn newton_system_with_jacobian<F, J, O>(fx: F, jacobian: J, xo: &[f64], options: O) -> Option<Vec<f64>>
+where
+    F: FnMut(&[f64]) -> Vec<f64>,
+    J: FnMut(&[f64]) -> Vec<Vec<f64>>,
+    O: Into<SolverOptions>,
+{
+    try_newton_system_with_jacobian(fx, jacobian, xo, options).ok().map(|r| r.root)
+}
+
+// same as newton_system_with_jacobian, but reports how it went, and why it failed if it did. The
+// evaluations in the report only count the calls to fx. A jacobian that isn't n by n is reported as
+// NotSquare too
+pub fn try_newton_system_with_jacobian<F, J, O>(fx: F, mut jacobian: J, xo: &[f64], options: O) -> Result<SystemReport, RootError>
+where
+    F: FnMut(&[f64]) -> Vec<f64>,
+    J: FnMut(&[f64]) -> Vec<Vec<f64>>,
+    O: Into<SolverOptions>,
+{
+    let options = options.into();
+    let mut fx = Counted::new(fx, options.max_evaluations);
+
+    newton_system_iterate(&mut fx, xo, options, |_, x| jacobian(x))
+}
+
+// the two flavours of broyden's update. Good updates the jacobian so it matches the last step and
+// changes as little as possible otherwise, Bad does the same to the inverse of the jacobian. Good
+// is usually the better one, hence the names
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BroydenUpdate {
+    #[default]
+    Good,
+    Bad,
+}
+
+// solves the system of equations fx(x) = 0, where fx goes from R^n to R^n, with broyden's
+// quasi-newton method starting at xo. The jacobian is only estimated with calculate_jacobian at
+// the start, after that each step updates it from the change in x and fx, so every iteration
+// costs a single call to fx instead of the 2n + 1 of newton_system. If the updated jacobian
+// stops giving steps that reduce |fx| it is estimated again. Returns None if it fails
+pub fn broyden_system<F, O>(fx: F, xo: &[f64], update: BroydenUpdate, options: O) -> Option<Vec<f64>>
+where
+    F: FnMut(&[f64]) -> Vec<f64>,
+    O: Into<SolverOptions>,
+{
+    try_broyden_system(fx, xo, update, options).ok().map(|r| r.root)
+}
+
+// same as broyden_system, but reports how it went, and why it failed if it did. A singular
+// jacobian is reported as ZeroDerivative, and a system with more or fewer equations than unknowns
+// as NotSquare. The inverse of the jacobian is what's kept around, the good update gets there with
+// the sherman-morrison formula, so no linear system is solved after the first iteration
+pub fn try_broyden_system<F, O>(fx: F, xo: &[f64], update: BroydenUpdate, options: O) -> Result<SystemReport, RootError>
+where
+    F: FnMut(&[f64]) -> Vec<f64>,
+    O: Into<SolverOptions>,
+{
+    let options = options.into();
+    let mut fx = Counted::new(fx, options.max_evaluations);
+
+    let mut xn = xo.to_vec();
+    let mut root = fx.eval_system(&xn);
+    if root.len() != xn.len() {
+        return Err(RootError::NotSquare);
+    }
+
+    let mut inverse = None;
+
+    for iterations in 0..=options.max_iterations {
+        if !all_finite(&xn) || !all_finite(&root) {
+            return Err(RootError::NonFinite);
+        }
+
+        if options.f_converged_all(&root) {
+            return Ok(fx.report_system(xn, root, iterations));
+        }
+
+        if iterations == options.max_iterations {
+            break;
+        }
+        fx.budget()?;
+
+        let fresh = inverse.is_none();
+        let h = match inverse.take() {
+            Some(h) => h,
+            None => {
+                let jac = calculate_jacobian(|x| fx.eval_system(x), &xn);
+                invert(jac).ok_or(RootError::ZeroDerivative)?
+            },
+        };
+
+        let step: Vec<f64> = mat_vec(&h, &root).iter().map(|s| -s).collect();
+
+        if options.x_converged_all(&xn, &step) {
+            let next = add_scaled(&xn, &step, 1.0);
+            let residual = fx.eval_system(&next);
+
+            if !all_finite(&residual) {
+                return Err(RootError::NonFinite);
+            }
+            return Ok(fx.report_system(next, residual, iterations + 1));
+        }
+
+        let merit = norm_sqr(&root);
+        let mut t = 1.0;
+        let mut next = add_scaled(&xn, &step, t);
+        let mut next_root = fx.eval_system(&next);
+        let mut decreased = false;
+
+        for _ in 0..MAX_BACKTRACKS {
+            let next_merit = norm_sqr(&next_root);
+            if !next_merit.is_nan() && next_merit <= (1.0 - 2.0*ARMIJO*t)*merit {
+                decreased = true;
+                break;
+            }
+
+            t /= 2.0;
+            next = add_scaled(&xn, &step, t);
+            next_root = fx.eval_system(&next);
+        }
+
+        // the old jacobian doesn't point downhill anymore, so get a fresh one and try again from
+        // the same point, unless it already was fresh
+        if !decreased && !fresh {
+            continue;
+        }
+
+        let dx: Vec<f64> = next.iter().zip(&xn).map(|(a, b)| a - b).collect();
+        let df: Vec<f64> = next_root.iter().zip(&root).map(|(a, b)| a - b).collect();
+        inverse = Some(broyden_update(h, &dx, &df, update));
+
+        xn = next;
+        root = next_root;
+    }
+
+    Err(RootError::MaxIterations)
+}
+
+// updates the inverse jacobian h after a step dx that changed the function by df. A degenerate
+// update (zero denominator) leaves h as it is
+fn broyden_update(mut h: Vec<Vec<f64>>, dx: &[f64], df: &[f64], update: BroydenUpdate) -> Vec<Vec<f64>> {
+    let h_df = mat_vec(&h, df);
+    let u: Vec<f64> = dx.iter().zip(&h_df).map(|(a, b)| a - b).collect();
+
+    // h += u*v^T/(v^T*df), with v = h^T*dx for the good update and v = df for the bad one
+    let v: Vec<f64> = match update {
+        BroydenUpdate::Good => (0..dx.len()).map(|j| (0..dx.len()).map(|i| dx[i]*h[i][j]).sum()).collect(),
+        BroydenUpdate::Bad => df.to_vec(),
+    };
+
+    let denominator: f64 = v.iter().zip(df).map(|(a, b)| a*b).sum();
+    if denominator == 0.0 || !denominator.is_finite() {
+        return h;
+    }
+
+    for (row, ui) in h.iter_mut().zip(&u) {
+        for (value, vj) in row.iter_mut().zip(&v) {
+            *value += ui*vj/denominator;
+        }
+    }
+
+    h
+}
+
+// the inverse of a, column by column with solve_linear, None if a is singular
+fn invert(a: Vec<Vec<f64>>) -> Option<Vec<Vec<f64>>> {
+    let n = a.len();
+    let mut columns = Vec::with_capacity(n);
+
+    for j in 0..n {
+        let e = (0..n).map(|i| if i == j { 1.0 } else { 0.0 }).collect();
+        columns.push(solve_linear(a.clone(), e)?);
+    }
+
+    Some((0..n).map(|i| columns.iter().map(|c| c[i]).collect()).collect())
+}
+
+fn mat_vec(a: &[Vec<f64>], x: &[f64]) -> Vec<f64> {
+    a.iter().map(|row| row.iter().zip(x).map(|(a, x)| a*x).sum()).collect()
+}
+
+// the sufficient decrease the line search asks for, in the armijo condition
+// |F(x + t*dx)|^2 <= (1 - 2*ARMIJO*t)*|F(x)|^2
+const ARMIJO: f64 = 1.0e-4;
+
+// the loop of the newton solvers of systems, the same as newton_iterate but the step comes from
+// solving J*dx = -F, and the backtracking asks for a sufficient decrease of |F|^2 instead of just
+// any decrease
+fn newton_system_iterate<F, J>(fx: &mut Counted<F>, xo: &[f64], options: SolverOptions, mut jacobian: J) -> Result<SystemReport, RootError>
+where
+    F: FnMut(&[f64]) -> Vec<f64>,
+    J: FnMut(&mut Counted<F>, &[f64]) -> Vec<Vec<f64>>,
+{
+    let mut xn = xo.to_vec();
+    let mut root = fx.eval_system(&xn);
+    if root.len() != xn.len() {
+        return Err(RootError::NotSquare);
+    }
+
+    for iterations in 0..=options.max_iterations {
+        if !all_finite(&xn) || !all_finite(&root) {
+            return Err(RootError::NonFinite);
+        }
+
+        if options.f_converged_all(&root) {
+            return Ok(fx.report_system(xn, root, iterations));
+        }
+
+        if iterations == options.max_iterations {
+            break;
+        }
+        fx.budget()?;
+
+        let jac = jacobian(fx, &xn);
+        if jac.len() != xn.len() || jac.iter().any(|row| row.len() != xn.len()) {
+            return Err(RootError::NotSquare);
+        } else if !jac.iter().all(|row| all_finite(row)) {
+            return Err(RootError::NonFinite);
+        }
+
+        let minus_root: Vec<f64> = root.iter().map(|f| -f).collect();
+        let step = solve_linear(jac, minus_root).ok_or(RootError::ZeroDerivative)?;
+
+        if options.x_converged_all(&xn, &step) {
+            let next = add_scaled(&xn, &step, 1.0);
+            let residual = fx.eval_system(&next);
+
+            if !all_finite(&residual) {
+                return Err(RootError::NonFinite);
+            }
+            return Ok(fx.report_system(next, residual, iterations + 1));
+        }
+
+        let merit = norm_sqr(&root);
+        let mut t = 1.0;
+        let mut next = add_scaled(&xn, &step, t);
+        let mut next_root = fx.eval_system(&next);
+
+        for _ in 0..MAX_BACKTRACKS {
+            let next_merit = norm_sqr(&next_root);
+            if !next_merit.is_nan() && next_merit <= (1.0 - 2.0*ARMIJO*t)*merit {
+                break;
+            }
+
+            t /= 2.0;
+            next = add_scaled(&xn, &step, t);
+            next_root = fx.eval_system(&next);
+        }
+
+        xn = next;
+        root = next_root;
+    }
+
+    Err(RootError::MaxIterations)
+}
+
+// solves a*x = b with gaussian elimination and partial pivoting, None if a is singular
+fn solve_linear(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Option<Vec<f64>> {
+    let n = b.len();
+
+    for k in 0..n {
+        let pivot = (k..n).max_by(|&i, &j| a[i][k].abs().total_cmp(&a[j][k].abs()))?;
+        if a[pivot][k] == 0.0 {
+            return None;
+        }
+
+        a.swap(k, pivot);
+        b.swap(k, pivot);
+
+        let (top, bottom) = a.split_at_mut(k + 1);
+        let pivot_row = &top[k];
+
+        for (i, row) in bottom.iter_mut().enumerate() {
+            let factor = row[k]/pivot_row[k];
+            for (value, pivot_value) in row[k..].iter_mut().zip(&pivot_row[k..]) {
+                *value -= factor*pivot_value;
+            }
+            b[k + 1 + i] -= factor*b[k];
+        }
+    }
+
+    let mut x = vec![0.0; n];
+    for k in (0..n).rev() {
+        let sum: f64 = ((k + 1)..n).map(|j| a[k][j]*x[j]).sum();
+        x[k] = (b[k] - sum)/a[k][k];
+    }
+
+    if all_finite(&x) { Some(x) } else { None }
+}
+
+// x + t*dx
+fn add_scaled(x: &[f64], dx: &[f64], t: f64) -> Vec<f64> {
+    x.iter().zip(dx).map(|(x, dx)| x + t*dx).collect()
+}
+
+fn norm_sqr(x: &[f64]) -> f64 {
+    x.iter().map(|x| x*x).sum()
+}
+
+fn all_finite(x: &[f64]) -> bool {
+    x.iter().all(|x| x.is_finite())
+}
+
+// wraps a function and counts how many times it was called, so the solvers can report it and
+// stop when they go over the evaluation budget
+struct Counted<F> {
+    fx: F,
+    evaluations: usize,
+    max_evaluations: usize,
+}
+
+impl<F> Counted<F> {
+    fn new(fx: F, max_evaluations: usize) -> Counted<F> {
+        Counted{fx, evaluations: 0, max_evaluations}
+    }
+
+    // checked once per iteration, so a solver may go over the budget by the cost of one iteration
+    fn budget(&self) -> Result<(), RootError> {
+        if self.evaluations >= self.max_evaluations {
+            return Err(RootError::MaxEvaluations);
+        }
+
+        Ok(())
+    }
+}
+
+impl<F> Counted<F>
+where
+    F: FnMut(f64) -> f64,
+{
+    fn eval(&mut self, x: f64) -> f64 {
+        self.evaluations += 1;
+        (self.fx)(x)
+    }
+
+    fn report(&self, root: f64, residual: f64, iterations: usize) -> RootReport {
+        RootReport{root, residual, iterations, evaluations: self.evaluations}
+    }
+}
+
+impl<F> Counted<F>
+where
+    F: FnMut(Complex) -> Complex,
+{
+    fn eval_complex(&mut self, z: Complex) -> Complex {
+        self.evaluations += 1;
+        (self.fx)(z)
+    }
+
+    fn report_complex(&self, root: Complex, residual: Complex, iterations: usize) -> RootReport<Complex> {
+        RootReport{root, residual, iterations, evaluations: self.evaluations}
+    }
+}
+
+impl<F> Counted<F>
+where
+    F: FnMut(&[f64]) -> Vec<f64>,
+{
+    fn eval_system(&mut self, x: &[f64]) -> Vec<f64> {
+        self.evaluations += 1;
+        (self.fx)(x)
+    }
+
+    fn report_system(&self, root: Vec<f64>, residual: Vec<f64>, iterations: usize) -> SystemReport {
+        SystemReport{root, residual, iterations, evaluations: self.evaluations}
+    }
+}
+
+//--------------------------------------------------------------------------------------------------
+//
+// CRATE TESTS
+//
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_newton_root() {
+        struct Test {
+            fx: fn(f64) -> f64,
+            xo: f64,
+            expect: f64,
+        }
+
+        let tests = vec![
+            Test{
+                fx: |x| {x.powf(2.0) + x - 6.0},
+                xo: 1.0,
+                expect: 2.0,
+            },
+            Test{
+                fx: |x| {x.powf(2.0) + x - 6.0},
+                xo: -2.0,
+                expect: -3.0,
+            },
+            Test{
+                fx: |x| {(x.powf(3.0)) + (-6.0*x.powf(2.0)) + (11.0*x) - 6.0},
+                xo: 0.5,
+                expect: 1.0,
+            },
+            Test{
+                fx: |x| {(x.powf(3.0)) + (-6.0*x.powf(2.0)) + (11.0*x) - 6.0},
+                xo: 2.2,
+                expect: 2.0,
+            },
+            Test{
+                fx: |x| {(x.powf(3.0)) + (-6.0*x.powf(2.0)) + (11.0*x) - 6.0},
+                xo: 4.0,
+                expect: 3.0,
+            },
+        ];
+
+        let precision = 1.0e-12;
+
+        for test in tests {
+            let root = newton_root(test.fx, test.xo, Some(precision));
+            assert!((root - test.expect).abs() < precision);
+        }
+    }
+
+    #[test]
+    fn test_bissec_root() {
+        struct Test {
+            fx: fn(f64) -> f64,
+            xmin: f64,
+            xmax: f64,
+            expect: f64,
+        }
+
+        let tests = vec![
+            Test{
+                fx: |x| {x.powf(2.0) + x - 6.0},
+                xmin: 0.0,
+                xmax: 4.0,
+                expect: 2.0,
+            },
+            Test{
+                fx: |x| {x.powf(2.0) + x - 6.0},
+                xmin: -5.0,
+                xmax: -1.0,
+                expect: -3.0,
+            },
+            Test{
+                fx: |x| {(x.powf(3.0)) + (-6.0*x.powf(2.0)) + (11.0*x) - 6.0},
+                xmin: 0.5,
+                xmax: 1.5,
+                expect: 1.0,
+            },
+            Test{
+                fx: |x| {(x.powf(3.0)) + (-6.0*x.powf(2.0)) + (11.0*x) - 6.0},
+                xmin: 1.5,
+                xmax: 2.5,
+                expect: 2.0,
+            },
+            Test{
+                fx: |x| {(x.powf(3.0)) + (-6.0*x.powf(2.0)) + (11.0*x) - 6.0},
+                xmin: 2.5,
+                xmax: 3.5,
+                expect: 3.0,
+            },
+            Test{
+                // f(xmin) is -1, which used to be taken as a root
+                fx: |x| {x - 3.0},
+                xmin: 2.0,
+                xmax: 4.0,
+                expect: 3.0,
+            },
+        ];
+
+        let precision = 1.0e-16;
+
+        for test in tests {
+            let root = bissec_root(test.fx, test.xmin, test.xmax, Some(precision)).unwrap();
+            assert!((root - test.expect).abs() < precision);
+        }
+    }
+
+    #[test]
+    fn test_root_closures_with_captured_parameters() {
+        let (a, b, c) = (1.0, 1.0, -6.0);
+        let precision = 1.0e-12;
+
+        let mut evaluations = 0;
+        let root = newton_root(|x| {
+            evaluations += 1;
+            a*x.powf(2.0) + b*x + c
+        }, 1.0, Some(precision));
+        assert!((root - 2.0).abs() < precision);
+        assert!(evaluations > 0);
+
+        let root = bissec_root(|x| a*x.powf(2.0) + b*x + c, -5.0, -1.0, Some(precision)).unwrap();
+        assert!((root - -3.0).abs() < precision);
+    }
+
+    #[test]
+    fn test_try_newton_root() {
+        struct Test {
+            fx: fn(f64) -> f64,
+            xo: f64,
+            expect: Result<f64, RootError>,
+        }
+
+        let tests = vec![
+            Test{
+                fx: |x| {x.powf(2.0) + x - 6.0},
+                xo: 1.0,
+                expect: Ok(2.0),
+            },
+            Test{
+                fx: |x| {(x.powf(3.0)) + (-6.0*x.powf(2.0)) + (11.0*x) - 6.0},
+                xo: 4.0,
+                expect: Ok(3.0),
+            },
+            Test{
+                fx: |x| {x.powf(2.0) + 1.0},
+                xo: 0.0,
+                expect: Err(RootError::ZeroDerivative),
+            },
+            Test{
+                fx: |x| {x.powf(2.0) + 1.0},
+                xo: 0.5,
+                expect: Err(RootError::MaxIterations),
+            },
+            Test{
+                // the full first step lands on a negative x, the backtracking brings it back
+                fx: |x| {x.ln()},
+                xo: 10.0,
+                expect: Ok(1.0),
+            },
+            Test{
+                fx: |x| {(x - 5.0).sqrt() - 1.0},
+                xo: 4.0,
+                expect: Err(RootError::NonFinite),
+            },
+        ];
+
+        let precision = 1.0e-12;
+
+        for test in tests {
+            let options = SolverOptions{f_abs_tol: precision, max_iterations: 100, ..Default::default()};
+            let result = try_newton_root(test.fx, test.xo, options);
+            match (result, test.expect) {
+                (Ok(report), Ok(expect)) => {
+                    assert!((report.root - expect).abs() < precision);
+                    assert!(report.residual.abs() <= precision);
+                    assert!(report.evaluations >= report.iterations);
+                },
+                (result, expect) => assert_eq!(result.map(|r| r.root), expect),
+            }
+        }
+
+        // with no root the steps keep backtracking all the way, so a budget of calls that doesn't
+        // account for it runs out before the iterations do
+        let options = SolverOptions{max_evaluations: 1000, ..Default::default()};
+        assert_eq!(try_newton_root(|x| x.powf(2.0) + 1.0, 0.5, options), Err(RootError::MaxEvaluations));
+        let options = SolverOptions{max_iterations: 1000, ..Default::default()};
+        assert_eq!(try_newton_root(|x| x.powf(2.0) + 1.0, 0.5, options), Err(RootError::MaxEvaluations));
+        assert_eq!(try_newton_root(|x| x.powf(2.0) + 1.0, 0.5, None), Err(RootError::MaxIterations));
+    }
+
+    #[test]
+    fn test_try_bissec_root() {
+        struct Test {
+            fx: fn(f64) -> f64,
+            xmin: f64,
+            xmax: f64,
+            max_iterations: usize,
+            expect: Result<f64, RootError>,
+        }
+
+        let tests = vec![
+            Test{
+                fx: |x| {x.powf(2.0) + x - 6.0},
+                xmin: 0.0,
+                xmax: 4.0,
+                max_iterations: 100,
+                expect: Ok(2.0),
+            },
+            Test{
+                fx: |x| {(x.powf(3.0)) + (-6.0*x.powf(2.0)) + (11.0*x) - 6.0},
+                xmin: 2.6,
+                xmax: 3.4,
+                max_iterations: 100,
+                expect: Ok(3.0),
+            },
+            Test{
+                fx: |x| {x.powf(2.0) + x - 6.0},
+                xmin: 2.5,
+                xmax: 4.0,
+                max_iterations: 100,
+                expect: Err(RootError::NoSignChange),
+            },
+            Test{
+                fx: |x| {(x.powf(3.0)) + (-6.0*x.powf(2.0)) + (11.0*x) - 6.0},
+                xmin: 2.7,
+                xmax: 3.4,
+                max_iterations: 5,
+                expect: Err(RootError::MaxIterations),
+            },
+            Test{
+                fx: |x| {1.0/x},
+                xmin: -1.0,
+                xmax: 1.0,
+                max_iterations: 100,
+                expect: Err(RootError::NonFinite),
+            },
+        ];
+
+        for test in tests {
+            let options = SolverOptions{max_iterations: test.max_iterations, ..Default::default()};
+            let result = try_bissec_root(test.fx, test.xmin, test.xmax, options);
+            match (result, test.expect) {
+                (Ok(report), Ok(expect)) => {
+                    assert!((report.root - expect).abs() < 1.0e-15);
+                    assert!(report.iterations <= test.max_iterations);
+                },
+                (result, expect) => assert_eq!(result.map(|r| r.root), expect),
+            }
+        }
+    }
+
+    #[test]
+    fn test_solver_options() {
+        struct Test {
+            fx: fn(f64) -> f64,
+            options: SolverOptions,
+            expect: Result<f64, RootError>,
+        }
+
+        // residuals of this one are huge even one ulp away from the root, so only the x tolerance
+        // can stop the solvers
+        let badly_scaled: fn(f64) -> f64 = |x| {1.0e20*(x.powf(2.0) - 2.0)};
+
+        let tests = vec![
+            Test{
+                fx: badly_scaled,
+                options: SolverOptions::default(),
+                expect: Ok(2.0_f64.sqrt()),
+            },
+            Test{
+                fx: badly_scaled,
+                options: SolverOptions{x_abs_tol: 1.0e-3, x_rel_tol: 0.0, ..Default::default()},
+                expect: Ok(2.0_f64.sqrt()),
+            },
+            Test{
+                fx: badly_scaled,
+                options: SolverOptions{max_evaluations: 4, ..Default::default()},
+                expect: Err(RootError::MaxEvaluations),
+            },
+            Test{
+                fx: badly_scaled,
+                options: SolverOptions{max_iterations: 3, ..Default::default()},
+                expect: Err(RootError::MaxIterations),
+            },
+        ];
+
+        for test in tests {
+            let newton = try_newton_root(test.fx, 1.0, test.options);
+            let bissec = try_bissec_root(test.fx, 1.0, 2.0, test.options);
+
+            for result in [newton, bissec] {
+                match (result, test.expect) {
+                    (Ok(report), Ok(expect)) => {
+                        let tolerance = test.options.x_abs_tol + test.options.x_rel_tol*expect;
+                        assert!((report.root - expect).abs() <= 2.0*tolerance);
+                        assert!(report.evaluations <= test.options.max_evaluations);
+                    },
+                    (result, expect) => assert_eq!(result.map(|r| r.root), expect),
+                }
+            }
+        }
+
+        assert_eq!(SolverOptions::from(None), SolverOptions::default());
+        assert_eq!(SolverOptions::from(Some(1.0e-9)).f_abs_tol, 1.0e-9);
+    }
+
+    #[test]
+    fn test_brent_root() {
+        struct Test {
+            fx: fn(f64) -> f64,
+            xmin: f64,
+            xmax: f64,
+            expect: Result<f64, RootError>,
+        }
+
+        let tests = vec![
+            Test{
+                fx: |x| {x.powf(2.0) + x - 6.0},
+                xmin: 0.0,
+                xmax: 4.0,
+                expect: Ok(2.0),
+            },
+            Test{
+                fx: |x| {(x.powf(3.0)) + (-6.0*x.powf(2.0)) + (11.0*x) - 6.0},
+                xmin: 0.3,
+                xmax: 1.6,
+                expect: Ok(1.0),
+            },
+            Test{
+                fx: |x| {x.cos() - x},
+                xmin: 0.0,
+                xmax: 1.0,
+                expect: Ok(0.7390851332151607),
+            },
+            Test{
+                fx: |x| {(x - 1.0).powf(3.0)},
+                xmin: -3.0,
+                xmax: 2.0,
+                expect: Ok(1.0),
+            },
+            Test{
+                fx: |x| {x.powf(2.0) + 1.0},
+                xmin: -1.0,
+                xmax: 1.0,
+                expect: Err(RootError::NoSignChange),
+            },
+        ];
+
+        for test in tests {
+            let result = try_brent_root(test.fx, test.xmin, test.xmax, None);
+            match (result, test.expect) {
+                (Ok(report), Ok(expect)) => {
+                    assert!((report.root - expect).abs() < 1.0e-5);
+                    assert_eq!(brent_root(test.fx, test.xmin, test.xmax, None), Some(report.root));
+                },
+                (result, expect) => assert_eq!(result.map(|r| r.root), expect),
+            }
+        }
+
+        // brent should get there with way fewer function calls than plain bisection
+        let fx = |x: f64| {x.cos() - x};
+        let brent = try_brent_root(fx, 0.0, 1.0, None).unwrap();
+        let bissec = try_bissec_root(fx, 0.0, 1.0, None).unwrap();
+        assert!((brent.root - 0.7390851332151607).abs() < 1.0e-15);
+        assert!(brent.evaluations*3 < bissec.evaluations);
+    }
+
+    #[test]
+    fn test_secant_root() {
+        struct Test {
+            fx: fn(f64) -> f64,
+            x0: f64,
+            x1: f64,
+            expect: Result<f64, RootError>,
+        }
+
+        let tests = vec![
+            Test{
+                fx: |x| {x.powf(2.0) + x - 6.0},
+                x0: 1.0,
+                x1: 1.5,
+                expect: Ok(2.0),
+            },
+            Test{
+                fx: |x| {(x.powf(3.0)) + (-6.0*x.powf(2.0)) + (11.0*x) - 6.0},
+                x0: 4.0,
+                x1: 3.8,
+                expect: Ok(3.0),
+            },
+            Test{
+                fx: |x| {x.cos() - x},
+                x0: 0.0,
+                x1: 1.0,
+                expect: Ok(0.7390851332151607),
+            },
+            Test{
+                fx: |x| {x.powf(2.0) + 1.0},
+                x0: -1.0,
+                x1: 1.0,
+                expect: Err(RootError::ZeroDerivative),
+            },
+        ];
+
+        for test in tests {
+            let result = try_secant_root(test.fx, test.x0, test.x1, None);
+            match (result, test.expect) {
+                (Ok(report), Ok(expect)) => {
+                    assert!((report.root - expect).abs() < 1.0e-14);
+                    assert_eq!(secant_root(test.fx, test.x0, test.x1, None), report.root);
+                },
+                (result, expect) => assert_eq!(result.map(|r| r.root), expect),
+            }
+        }
+    }
+
+    #[test]
+    fn test_bracket_methods() {
+        struct Test {
+            fx: fn(f64) -> f64,
+            xmin: f64,
+            xmax: f64,
+            expect: Result<f64, RootError>,
+        }
+
+        let tests = vec![
+            Test{
+                fx: |x| {x.powf(2.0) + x - 6.0},
+                xmin: 0.0,
+                xmax: 4.0,
+                expect: Ok(2.0),
+            },
+            Test{
+                fx: |x| {(x.powf(3.0)) + (-6.0*x.powf(2.0)) + (11.0*x) - 6.0},
+                xmin: 2.6,
+                xmax: 3.7,
+                expect: Ok(3.0),
+            },
+            Test{
+                // convex all the way, plain regula falsi would keep xmax forever here
+                fx: |x| {x.exp() - 10.0},
+                xmin: 0.0,
+                xmax: 5.0,
+                expect: Ok(10.0_f64.ln()),
+            },
+            Test{
+                fx: |x| {x.cos() - x},
+                xmin: 0.0,
+                xmax: 1.0,
+                expect: Ok(0.7390851332151607),
+            },
+            Test{
+                fx: |x| {x.powf(2.0) + x - 6.0},
+                xmin: 2.5,
+                xmax: 4.0,
+                expect: Err(RootError::NoSignChange),
+            },
+        ];
+
+        let methods = [BracketMethod::Bisection, BracketMethod::Brent, BracketMethod::Illinois, BracketMethod::Ridders];
+
+        for test in tests {
+            for method in methods {
+                let result = method.solve(test.fx, test.xmin, test.xmax, None);
+                match (result, test.expect) {
+                    (Ok(report), Ok(expect)) => {
+                        assert!((report.root - expect).abs() < 1.0e-14, "{:?} {}", method, report.root);
+                    },
+                    (result, expect) => assert_eq!(result.map(|r| r.root), expect),
+                }
+            }
+
+            assert_eq!(regula_falsi_root(test.fx, test.xmin, test.xmax, None), try_regula_falsi_root(test.fx, test.xmin, test.xmax, None).ok().map(|r| r.root));
+            assert_eq!(ridders_root(test.fx, test.xmin, test.xmax, None), try_ridders_root(test.fx, test.xmin, test.xmax, None).ok().map(|r| r.root));
+        }
+    }
+
+    #[test]
+    fn test_newton_root_with_derivatives() {
+        struct Test {
+            fx: fn(f64) -> f64,
+            dfx: fn(f64) -> f64,
+            d2fx: fn(f64) -> f64,
+            xo: f64,
+            expect: f64,
+        }
+
+        let tests = vec![
+            Test{
+                fx: |x| {x.powf(2.0) + x - 6.0},
+                dfx: |x| {2.0*x + 1.0},
+                d2fx: |_| {2.0},
+                xo: 1.0,
+                expect: 2.0,
+            },
+            Test{
+                fx: |x| {(x.powf(3.0)) + (-6.0*x.powf(2.0)) + (11.0*x) - 6.0},
+                dfx: |x| {3.0*x.powf(2.0) - 12.0*x + 11.0},
+                d2fx: |x| {6.0*x - 12.0},
+                xo: 4.0,
+                expect: 3.0,
+            },
+            Test{
+                // newton overshoots a lot on arctan from here and diverges without backtracking
+                fx: |x| {x.atan()},
+                dfx: |x| {1.0/(1.0 + x.powf(2.0))},
+                d2fx: |x| {-2.0*x/(1.0 + x.powf(2.0)).powf(2.0)},
+                xo: 3.0,
+                expect: 0.0,
+            },
+        ];
+
+        for test in tests {
+            let newton = try_newton_root_with_derivative(test.fx, test.dfx, test.xo, None).unwrap();
+            let halley = try_halley_root(test.fx, test.dfx, test.d2fx, test.xo, None).unwrap();
+
+            assert!((newton.root - test.expect).abs() < 1.0e-14);
+            assert!((halley.root - test.expect).abs() < 1.0e-14);
+            assert!(halley.evaluations <= newton.evaluations);
+
+            assert_eq!(newton_root_with_derivative(test.fx, test.dfx, test.xo, None), newton.root);
+            assert_eq!(halley_root(test.fx, test.dfx, test.d2fx, test.xo, None), halley.root);
+        }
+
+        let result = try_newton_root_with_derivative(|x| x.powf(2.0) + 1.0, |x| 2.0*x, 0.0, None);
+        assert_eq!(result, Err(RootError::ZeroDerivative));
+    }
+
+    #[test]
+    fn test_solver_observers() {
+        let fx = |x: f64| x.powf(2.0) - 2.0;
+
+        // the observer sees every iteration the report counts, and the last one is the root
+        let mut trace = Vec::new();
+        let newton = try_newton_root_observed(fx, 3.0, None, &mut |it: &Iteration| {trace.push(*it); true}).unwrap();
+        assert_eq!(trace.len(), newton.iterations);
+        assert_eq!(trace.last().unwrap().x, newton.root);
+        assert!(trace.iter().all(|it| it.bracket.is_none()));
+        assert_eq!(newton, try_newton_root(fx, 3.0, None).unwrap());
+
+        let mut trace = Vec::new();
+        let bissec = try_bissec_root_observed(fx, 0.0, 3.0, None, &mut |it: &Iteration| {trace.push(*it); true}).unwrap();
+        assert_eq!(trace.len(), bissec.iterations);
+        assert_eq!(bissec, try_bissec_root(fx, 0.0, 3.0, None).unwrap());
+
+        for (it, next) in trace.iter().zip(&trace[1..]) {
+            let (a, b) = it.bracket.unwrap();
+            assert!(fx(a) < 0.0 && fx(b) > 0.0);
+            assert_eq!(next.step, it.step/2.0);
+        }
+
+        // stopping early isn't converging, what the solver had at that point is the last iteration
+        // the observer saw
+        let mut last = None;
+        let stopped = try_bissec_root_observed(fx, 0.0, 3.0, None, &mut |it: &Iteration| {last = Some(*it); it.step > 1.0e-3});
+        assert_eq!(stopped, Err(RootError::Stopped));
+        assert!(last.unwrap().iteration < bissec.iterations);
+        assert!((last.unwrap().x - 2.0_f64.sqrt()).abs() < 1.0e-3);
+
+        let stopped = try_newton_root_observed(fx, 3.0, None, &mut |it: &Iteration| it.iteration < 2);
+        assert_eq!(stopped, Err(RootError::Stopped));
+
+        // but stopping on the iteration that converges still gives the root
+        let newton_last = newton.iterations;
+        let stopped = try_newton_root_observed(fx, 3.0, None, &mut |it: &Iteration| it.iteration < newton_last);
+        assert_eq!(stopped, Ok(newton));
+
+        // and the same for the rest of the solvers
+        struct Test {
+            solve: fn(&mut dyn Observer) -> Result<RootReport, RootError>,
+            bracketing: bool,
+        }
+
+        let tests = vec![
+            Test{
+                solve: |observer| {try_newton_root_with_derivative_observed(|x| x.powf(2.0) - 2.0, |x| 2.0*x, 3.0, None, observer)},
+                bracketing: false,
+            },
+            Test{
+                solve: |observer| {try_halley_root_observed(|x| x.powf(2.0) - 2.0, |x| 2.0*x, |_| 2.0, 3.0, None, observer)},
+                bracketing: false,
+            },
+            Test{
+                solve: |observer| {try_secant_root_observed(|x| x.powf(2.0) - 2.0, 3.0, 2.5, None, observer)},
+                bracketing: false,
+            },
+            Test{
+                solve: |observer| {try_brent_root_observed(|x| x.powf(2.0) - 2.0, 0.0, 3.0, None, observer)},
+                bracketing: true,
+            },
+            Test{
+                solve: |observer| {try_regula_falsi_root_observed(|x| x.powf(2.0) - 2.0, 0.0, 3.0, None, observer)},
+                bracketing: true,
+            },
+            Test{
+                solve: |observer| {try_ridders_root_observed(|x| x.powf(2.0) - 2.0, 0.0, 3.0, None, observer)},
+                bracketing: true,
+            },
+            Test{
+                solve: |observer| {try_modified_newton_root_observed(|x| (x.powf(2.0) - 2.0)*(x - 2.0_f64.sqrt()), 3.0, None, observer).map(|(r, _)| r)},
+                bracketing: false,
+            },
+            Test{
+                solve: |observer| {try_fixed_point_observed(|x| x - (x.powf(2.0) - 2.0)/4.0, 3.0, FixedPointAcceleration::Steffensen, None, observer)},
+                bracketing: false,
+            },
+        ];
+
+        for test in tests {
+            let mut trace = Vec::new();
+            let report = (test.solve)(&mut |it: &Iteration| {trace.push(*it); true}).unwrap();
+            assert_eq!(trace.len(), report.iterations);
+            assert_eq!(trace.last().unwrap().x, report.root);
+
+            for it in &trace {
+                match it.bracket {
+                    Some((a, b)) => assert!(test.bracketing && fx(a) <= 0.0 && fx(b) >= 0.0, "{:?}", it),
+                    None => assert!(!test.bracketing),
+                }
+            }
+
+            let mut seen = 0;
+            let stopped = (test.solve)(&mut |it: &Iteration| {seen = it.iteration; it.iteration < 2});
+            assert_eq!(stopped, Err(RootError::Stopped));
+            assert_eq!(seen, 2);
+
+            let last = report.iterations;
+            assert_eq!((test.solve)(&mut |it: &Iteration| it.iteration < last), Ok(report));
+        }
+
+        // the logger writes one row per iteration, with 5 columns
+        let path = std::env::temp_dir().join(format!("numeric_calc_test_trace_{}.dat", std::process::id()));
+        let mut logger = TraceLogger::new(DataFile::create(path.to_str().unwrap()));
+        try_bissec_root_observed(fx, 0.0, 3.0, None, &mut logger).unwrap();
+        drop(logger);
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(contents.lines().count(), bissec.iterations);
+        assert!(contents.lines().all(|line| line.split_whitespace().count() == 5));
+        assert!(contents.starts_with(" 1 1.5 0.25 1.5 1.5\n"));
+    }
+
+    #[test]
+    fn test_solver_steps() {
+        let fx = |x: f64| x.powf(2.0) - 2.0;
+
+        // driven to the end the iterators land where the closed loops do
+        let newton = newton_steps(fx, 3.0).last().unwrap();
+        assert!((newton.x - 2.0_f64.sqrt()).abs() < 1.0e-15);
+
+        let bissec = bissec_steps(fx, 0.0, 3.0).unwrap().last().unwrap();
+        assert!((bissec.x - 2.0_f64.sqrt()).abs() < 1.0e-15);
+
+        // the same iterates the closed loops take, the bisection just goes on until the bracket can't
+        // be split anymore instead of stopping at the default tolerance
+        let mut trace = Vec::new();
+        try_newton_root_observed(fx, 3.0, None, &mut |it: &Iteration| {trace.push(*it); true}).unwrap();
+        assert!(newton_steps(fx, 3.0).zip(&trace).all(|(a, b)| a == *b));
+
+        let mut trace = Vec::new();
+        try_bissec_root_observed(fx, 0.0, 3.0, None, &mut |it: &Iteration| {trace.push(*it); true}).unwrap();
+        assert!(bissec_steps(fx, 0.0, 3.0).unwrap().zip(&trace).all(|(a, b)| a == *b));
+        assert!(bissec_steps(fx, 0.0, 3.0).unwrap().count() > trace.len());
+
+        // stopping on our own criteria
+        let steps: Vec<Iteration> = bissec_steps(fx, 0.0, 3.0).unwrap().take_while(|it| it.step > 1.0e-6).collect();
+        let (a, b) = steps.last().unwrap().bracket.unwrap();
+        assert!(a < 2.0_f64.sqrt() && 2.0_f64.sqrt() < b);
+        assert!(b - a < 2.0e-6 && b - a > 1.0e-7);
+
+        let close = newton_steps(|x| x.cos() - x, 1.0).find(|it| it.residual.abs() < 1.0e-10).unwrap();
+        assert!((close.x - 0.7390851332151607).abs() < 1.0e-10);
+
+        // the endings
+        assert_eq!(bissec_steps(fx, 2.0, 3.0).err(), Some(RootError::NoSignChange));
+        assert_eq!(bissec_steps(|x| x - 1.0, 1.0, 3.0).unwrap().map(|it| it.x).collect::<Vec<f64>>(), vec![1.0]);
+        assert_eq!(newton_steps(|x| x.powf(2.0) + 1.0, 0.0).count(), 0);
+        assert_eq!(newton_steps(|x| x - 1.0, 1.0).count(), 0);
+
+        // once it has ended the iterator doesn't call the function anymore
+        let calls = std::cell::Cell::new(0);
+        let mut steps = newton_steps(|x| {calls.set(calls.get() + 1); x.powf(2.0) + 1.0}, 0.0);
+        assert!(steps.next().is_none());
+        let ended = calls.get();
+        assert!((0..5).all(|_| steps.next().is_none()));
+        assert_eq!(calls.get(), ended);
 
-    while root.abs() > precision {
-        xn = xn - (fx(xn)/calculate_derivative(fx, xn));
-        root = fx(xn);
+        let calls = std::cell::Cell::new(0);
+        let mut steps = bissec_steps(|x| {calls.set(calls.get() + 1); x - 1.0}, 1.0, 3.0).unwrap();
+        assert!(steps.next().is_some() && steps.next().is_none());
+        let ended = calls.get();
+        assert!((0..5).all(|_| steps.next().is_none()));
+        assert_eq!(calls.get(), ended);
     }
 
-    xn
-}
-
-// calculates the many roots of a certain function at a certain point with an optional given precision
-// sometimes the precision may be too high, and the root never converges to this extreme precise
-// point, so you may have to lower it. The number of intervals you choose may impact the result,
-// but it is usually a good idea to have a lot of intervals, because the roots are usually
-// close to each other, and the precision is usually high enough to find them in a few steps
-pub fn bissec_root_many(fx: fn(f64) -> f64, xmin: f64, xmax: f64, num_intervals: i32, precision: Option<f64>) -> Vec<f64> {
-    let delx = (xmax - xmin)/num_intervals as f64;
+    #[test]
+    fn test_interval_newton() {
+        struct Test {
+            fx: fn(Interval) -> Interval,
+            dfx: fn(Interval) -> Interval,
+            x: Interval,
+            verified: Vec<f64>,
+            unresolved: Vec<f64>,
+        }
 
-    let roots = Vec::new();
-    let roots_ref = Arc::new(RwLock::new(roots));
-    let mut threads = Vec::new();
+        let pi = std::f64::consts::PI;
 
-    for i in -1..num_intervals {
-        let roots_ref = roots_ref.clone();
+        let tests = vec![
+            Test{
+                fx: |x| {x.powi(2) - 2.0},
+                dfx: |x| {2.0*x},
+                x: Interval::new(0.0, 3.0),
+                verified: vec![2.0_f64.sqrt()],
+                unresolved: vec![],
+            },
+            Test{
+                fx: |x| {x.sin()},
+                dfx: |x| {x.cos()},
+                x: Interval::new(-4.0, 10.0),
+                verified: vec![-pi, 0.0, pi, 2.0*pi, 3.0*pi],
+                unresolved: vec![],
+            },
+            Test{
+                fx: |x| {x.exp() - 2.0 - x},
+                dfx: |x| {x.exp() - 1.0},
+                x: Interval::new(-5.0, 5.0),
+                verified: vec![-1.8414056604369606, 1.1461932206205825],
+                unresolved: vec![],
+            },
+            Test{
+                // close but distinct roots are still told apart
+                fx: |x| {(x - 1.0)*(x - 1.000001)},
+                dfx: |x| {2.0*x - 2.000001},
+                x: Interval::new(0.0, 2.0),
+                verified: vec![1.0, 1.000001],
+                unresolved: vec![],
+            },
+            Test{
+                // a double root can't be proven unique, it's left unresolved
+                fx: |x| {(x - 1.0).powi(2)},
+                dfx: |x| {2.0*(x - 1.0)},
+                x: Interval::new(0.0, 3.0),
+                verified: vec![],
+                unresolved: vec![1.0],
+            },
+            Test{
+                fx: |x| {x.powi(2) + 1.0},
+                dfx: |x| {2.0*x},
+                x: Interval::new(-3.0, 3.0),
+                verified: vec![],
+                unresolved: vec![],
+            },
+            Test{
+                // only defined on part of the box, the rest is dropped
+                fx: |x| {x.sqrt() - 1.0},
+                dfx: |x| {0.5/x.sqrt()},
+                x: Interval::new(-4.0, 4.0),
+                verified: vec![1.0],
+                unresolved: vec![],
+            },
+            Test{
+                fx: |x| {x.ln()},
+                dfx: |x| {1.0/x},
+                x: Interval::new(-1.0, 3.0),
+                verified: vec![1.0],
+                unresolved: vec![],
+            },
+            Test{
+                // 2x + 0.5 where it is defined, with a root at -0.25 where it isn't. The first
+                // newton step lands there, which proves nothing since f isn't defined on the box
+                fx: |x| {x + 0.5 + x.sqrt()*x.sqrt()},
+                dfx: |_| {Interval::point(2.0)},
+                x: Interval::new(-1.0, 1.0),
+                verified: vec![],
+                unresolved: vec![],
+            },
+        ];
 
-        let thread = thread::spawn(move || {
-            let xmin_temp = xmin + (i as f64 * delx);
-            let xmax_temp = xmin + ((i+0) as f64 * delx);
-            let roots_ref = roots_ref;
+        for test in tests {
+            let enclosures = interval_newton(test.fx, test.dfx, test.x, None);
+            assert_eq!(enclosures.verified.len(), test.verified.len(), "{:?}", enclosures);
+            assert_eq!(enclosures.unresolved.len(), test.unresolved.len(), "{:?}", enclosures);
 
-            let root = bissec_root(fx, xmin_temp, xmax_temp, precision);
-            match root {
-                Some(v) => roots_ref.write().unwrap().push(v),
-                None => {},
+            for (enclosure, root) in enclosures.verified.iter().zip(test.verified) {
+                assert!(enclosure.width() < 1.0e-14*(1.0 + root.abs()), "{}", enclosure);
+                assert!((enclosure.mid() - root).abs() < 1.0e-14*(1.0 + root.abs()), "{} {}", enclosure, root);
             }
-        });
 
-        threads.push(thread);
-    }
+            for (enclosure, root) in enclosures.unresolved.iter().zip(test.unresolved) {
+                assert!(enclosure.contains(root) && enclosure.width() < 1.0e-6, "{}", enclosure);
+            }
+        }
 
-    for thread in threads {
-        thread.join().unwrap();
-    }
+        // unbounded boxes are searched a piece at a time
+        let enclosures = interval_newton(|x| x.powi(2) - 2.0, |x| 2.0*x, Interval::new(0.0, f64::INFINITY), None);
+        assert_eq!(enclosures.verified.len(), 1);
+        assert!(enclosures.verified[0].contains(2.0_f64.sqrt()) && enclosures.unresolved.is_empty());
 
-    let mut result = Arc::try_unwrap(roots_ref).unwrap().into_inner().unwrap();
-    result.dedup();
-    result.sort_by(|a, b| a.partial_cmp(b).unwrap());
-    result
-}
+        let enclosures = interval_newton(|x| x.exp() - 2.0 - x, |x| x.exp() - 1.0, Interval::ENTIRE, None);
+        assert_eq!(enclosures.verified.len(), 2, "{:?}", enclosures);
+        assert!(enclosures.verified[0].contains(-1.8414056604369606) && enclosures.verified[1].contains(1.1461932206205825));
 
-// calculates the root of a certain function at a certain point with an optional given precision
-// sometimes the precision may be too high, and the root never converges to this extreme precise
-// point, so you may have to lower it.
-pub fn bissec_root(fx: fn(f64) -> f64, mut xmin: f64, mut xmax: f64, precision: Option<f64>) -> Option<f64> {
-    let precision = precision.unwrap_or(1.0e-16);
-    let mut xmed = (xmin + xmax)/2.0;
+        // and with infinitely many roots the evaluations run out, but only after finding a lot of them
+        let enclosures = interval_newton(|x| x.sin(), |x| x.cos(), Interval::new(1.0, f64::INFINITY), None);
+        assert!(enclosures.verified.len() > 10 && !enclosures.unresolved.is_empty());
+        assert!(enclosures.verified.iter().all(|x| x.width() < 1.0e-10 && x.sin().contains(0.0)));
 
-    if fx(xmin) == -1.0 {
-        return Some(xmin);
-    } else if fx(xmax) == -1.0 {
-        return Some(xmax);
+        // the enclosure really holds the root, sqrt(2) is between these two floats
+        let enclosure = interval_newton(|x| x.powi(2) - 2.0, |x| 2.0*x, Interval::new(1.0, 2.0), None).verified[0];
+        let (below, above) = (2.0_f64.sqrt().next_down(), 2.0_f64.sqrt().next_up());
+        assert!(enclosure.lo <= below && above <= enclosure.hi);
     }
 
-    if calculate_sign_change(fx, xmin, xmax) {
-        while fx(xmed).abs() > precision {
-            if calculate_sign_change(fx, xmin, xmed) {
-                xmax = xmed;
-            }
-            else if calculate_sign_change(fx, xmed, xmax) {
-                xmin = xmed;
-            }
-
-            xmed = (xmin + xmax)/2.0;
+    #[test]
+    fn test_modified_newton_root() {
+        struct Test {
+            fx: fn(f64) -> f64,
+            xo: f64,
+            expect: f64,
+            multiplicity: usize,
+            tol: f64,
         }
-    } else {
-        return None;
-    }
 
-    Some(xmed)
-}
+        let tests = vec![
+            Test{
+                fx: |x| {(x - 2.0).powf(2.0)},
+                xo: 5.0,
+                expect: 2.0,
+                multiplicity: 2,
+                tol: 1.0e-8,
+            },
+            Test{
+                fx: |x| {(x - 1.0).powf(2.0)*x.exp()},
+                xo: 3.0,
+                expect: 1.0,
+                multiplicity: 2,
+                tol: 1.0e-7,
+            },
+            Test{
+                fx: |x| {(x - 1.0).powi(3)*(x + 2.0)},
+                xo: 2.0,
+                expect: 1.0,
+                multiplicity: 3,
+                tol: 1.0e-4,
+            },
+            Test{
+                fx: |x| {x.sin().powi(4)},
+                xo: 0.5,
+                expect: 0.0,
+                multiplicity: 4,
+                tol: 1.0e-3,
+            },
+            Test{
+                // plain newton halves its steps far away from a simple root too
+                fx: |x| {x.powf(2.0) - 4.0},
+                xo: 100.0,
+                expect: 2.0,
+                multiplicity: 1,
+                tol: 1.0e-14,
+            },
+        ];
 
-// calculates the sign_chane, there is an actual better way to do this, like just multiplying the
-// two numbers and checking if it is negative, but i kinda like this one hehe
-fn calculate_sign_change(fx: fn(f64) -> f64, xmin: f64, xmax: f64) -> bool {
-    (fx(xmin) + fx(xmax)).abs() < (fx(xmin).abs() + fx(xmax).abs())
-}
+        for test in tests {
+            let (report, multiplicity) = try_modified_newton_root(test.fx, test.xo, None).unwrap();
 
-//--------------------------------------------------------------------------------------------------
-//
-// CRATE TESTS
-//
-//--------------------------------------------------------------------------------------------------
+            assert!((report.root - test.expect).abs() < test.tol, "{} {}", report.root, test.expect);
+            assert_eq!(multiplicity, test.multiplicity, "{:?}", report);
+            assert_eq!(modified_newton_root(test.fx, test.xo, None), Some((report.root, multiplicity)));
+        }
 
-#[cfg(test)]
-mod tests {
-    use crate::*;
+        // a double root at 1 and a simple one at 1.001 look like a triple root from far away, the
+        // solver settles on one of them with its own multiplicity, depending on where it starts
+        let cluster = |x: f64| (x - 1.0).powf(2.0)*(x - 1.001);
+        let (root, multiplicity) = modified_newton_root(cluster, 3.0, None).unwrap();
+        assert!((root - 1.0).abs() < 1.0e-8 && multiplicity == 2, "{} {}", root, multiplicity);
+        let (root, multiplicity) = modified_newton_root(cluster, 1.0008, None).unwrap();
+        assert!((root - 1.001).abs() < 1.0e-14 && multiplicity == 1, "{} {}", root, multiplicity);
+
+        // evaluated in single precision, f is all noise long before the steps get below the
+        // eps^(1/3) of a f64, and that's reported instead of a root
+        let single = |x: f64| ((x as f32 - 1.0).powi(3)*(x as f32 + 2.0)) as f64;
+        assert_eq!(try_modified_newton_root(single, 3.0, None).map(|(r, _)| r.root), Err(RootError::Stalled));
+
+        // plain newton crawls towards the double root, modified newton gets there in a few steps
+        let plain = try_newton_root(|x| (x - 2.0).powf(2.0)*(x + 1.0), 5.0, None);
+        let (modified, _) = try_modified_newton_root(|x| (x - 2.0).powf(2.0)*(x + 1.0), 5.0, None).unwrap();
+        assert!(plain.map_or(true, |plain| modified.evaluations < plain.evaluations));
+    }
 
     #[test]
-    fn test_newton_root() {
+    fn test_find_bracket() {
         struct Test {
             fx: fn(f64) -> f64,
             xo: f64,
-            expect: f64,
+            expect: Result<f64, RootError>,
         }
 
         let tests = vec![
             Test{
                 fx: |x| {x.powf(2.0) + x - 6.0},
-                xo: 1.0,
-                expect: 2.0,
+                xo: 0.0,
+                expect: Ok(2.0),
             },
             Test{
                 fx: |x| {x.powf(2.0) + x - 6.0},
-                xo: -2.0,
-                expect: -3.0,
+                xo: -1.0,
+                expect: Ok(-3.0),
             },
             Test{
-                fx: |x| {(x.powf(3.0)) + (-6.0*x.powf(2.0)) + (11.0*x) - 6.0},
+                fx: |x| {x.exp() - 1.0e3},
+                xo: -20.0,
+                expect: Ok(1.0e3_f64.ln()),
+            },
+            Test{
+                // the left side leaves the domain of sqrt and is dropped
+                fx: |x| {x.sqrt() - 30.0},
                 xo: 0.5,
-                expect: 1.0,
+                expect: Ok(900.0),
             },
             Test{
-                fx: |x| {(x.powf(3.0)) + (-6.0*x.powf(2.0)) + (11.0*x) - 6.0},
-                xo: 2.2,
-                expect: 2.0,
+                fx: |x| {x.powf(2.0) + 1.0},
+                xo: 0.0,
+                expect: Err(RootError::NoSignChange),
             },
             Test{
-                fx: |x| {(x.powf(3.0)) + (-6.0*x.powf(2.0)) + (11.0*x) - 6.0},
-                xo: 4.0,
-                expect: 3.0,
+                fx: |x| {x.sqrt()},
+                xo: -1.0,
+                expect: Err(RootError::NonFinite),
             },
         ];
 
-        let precision = 1.0e-12;
-
         for test in tests {
-            let root = newton_root(test.fx, test.xo, Some(precision));
-            assert!((root - test.expect).abs() < precision);
+            let result = try_find_bracket(test.fx, test.xo, None);
+            match (result, test.expect) {
+                (Ok((xmin, xmax)), Ok(expect)) => {
+                    assert!(xmin <= expect && expect <= xmax);
+                    let root = bissec_root(test.fx, xmin, xmax, None).unwrap();
+                    assert!((root - expect).abs() < 1.0e-12);
+                },
+                (result, expect) => assert_eq!(result.map(|(xmin, _)| xmin), expect),
+            }
         }
+
+        let search = BracketSearch{max_evaluations: 5, ..Default::default()};
+        assert_eq!(try_find_bracket(|x| x - 100.0, 0.0, Some(search)), Err(RootError::MaxEvaluations));
+        assert_eq!(find_bracket(|x| x.powf(2.0) + 1.0, 0.0, None), None);
     }
 
     #[test]
-    fn test_bissec_root() {
+    fn test_bissec_root_many() {
         struct Test {
             fx: fn(f64) -> f64,
             xmin: f64,
             xmax: f64,
-            expect: f64,
+            num_intervals: i32,
+            expect: Vec<f64>,
         }
 
         let tests = vec![
             Test{
+                // the roots sit exactly on the ends of the subintervals, so they are found twice
                 fx: |x| {x.powf(2.0) + x - 6.0},
+                xmin: -5.0,
+                xmax: 5.0,
+                num_intervals: 10,
+                expect: vec![-3.0, 2.0],
+            },
+            Test{
+                fx: |x| {(x.powf(3.0)) + (-6.0*x.powf(2.0)) + (11.0*x) - 6.0},
                 xmin: 0.0,
                 xmax: 4.0,
-                expect: 2.0,
+                num_intervals: 7,
+                expect: vec![1.0, 2.0, 3.0],
             },
             Test{
-                fx: |x| {x.powf(2.0) + x - 6.0},
-                xmin: -5.0,
-                xmax: -1.0,
-                expect: -3.0,
+                fx: |x| {x.sin()},
+                xmin: -0.5,
+                xmax: 10.0,
+                num_intervals: 33,
+                expect: vec![0.0, std::f64::consts::PI, 2.0*std::f64::consts::PI, 3.0*std::f64::consts::PI],
             },
             Test{
-                fx: |x| {(x.powf(3.0)) + (-6.0*x.powf(2.0)) + (11.0*x) - 6.0},
-                xmin: 0.5,
-                xmax: 1.5,
-                expect: 1.0,
+                // a single subinterval used to have zero width and find nothing
+                fx: |x| {x - 0.3},
+                xmin: 0.0,
+                xmax: 1.0,
+                num_intervals: 1,
+                expect: vec![0.3],
             },
             Test{
-                fx: |x| {(x.powf(3.0)) + (-6.0*x.powf(2.0)) + (11.0*x) - 6.0},
-                xmin: 1.5,
-                xmax: 2.5,
-                expect: 2.0,
+                fx: |x| {(x - 2.0).powf(2.0)},
+                xmin: 0.0,
+                xmax: 5.0,
+                num_intervals: 7,
+                expect: vec![],
+            },
+        ];
+
+        for test in tests {
+            let roots = bissec_root_many(test.fx, test.xmin, test.xmax, test.num_intervals, None);
+            assert_eq!(roots.len(), test.expect.len());
+
+            for (root, expect) in roots.iter().zip(test.expect) {
+                assert!((root - expect).abs() < 1.0e-14);
+            }
+        }
+    }
+
+    #[test]
+    fn test_bracket_root_many_tangent_roots() {
+        struct Test {
+            fx: fn(f64) -> f64,
+            xmin: f64,
+            xmax: f64,
+            num_intervals: i32,
+            expect: Vec<f64>,
+        }
+
+        let tests = vec![
+            Test{
+                fx: |x| {(x - 2.0).powf(2.0)},
+                xmin: 0.0,
+                xmax: 5.0,
+                num_intervals: 7,
+                expect: vec![2.0],
             },
             Test{
-                fx: |x| {(x.powf(3.0)) + (-6.0*x.powf(2.0)) + (11.0*x) - 6.0},
-                xmin: 2.5,
-                xmax: 3.5,
-                expect: 3.0,
+                fx: |x| {(x - 1.0).powf(2.0)*(x - 3.0)},
+                xmin: -0.3,
+                xmax: 4.0,
+                num_intervals: 9,
+                expect: vec![1.0, 3.0],
+            },
+            Test{
+                // the minimum of |f| is not a zero here, so nothing should come out
+                fx: |x| {(x - 2.0).powf(2.0) + 0.1},
+                xmin: 0.0,
+                xmax: 5.0,
+                num_intervals: 7,
+                expect: vec![],
             },
         ];
 
-        let precision = 1.0e-16;
+        let scan = ScanOptions{tangent_roots: true, ..Default::default()};
 
         for test in tests {
-            let root = bissec_root(test.fx, test.xmin, test.xmax, Some(precision)).unwrap();
-            assert!((root - test.expect).abs() < precision);
+            for method in [BracketMethod::Bisection, BracketMethod::Brent] {
+                let scan = ScanOptions{method, ..scan};
+                let roots = bracket_root_many(test.fx, test.xmin, test.xmax, test.num_intervals, scan, None);
+                assert_eq!(roots.len(), test.expect.len());
+
+                for (root, expect) in roots.iter().zip(&test.expect) {
+                    assert!((root - expect).abs() < 1.0e-8);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_bracket_root_many_threads() {
+        let fx = |x: f64| {(x/10.0).sin()};
+        let expect: Vec<f64> = (0..=31).map(|k| 10.0*std::f64::consts::PI*k as f64).collect();
+
+        // way more subintervals than threads, this used to spawn a thread for each one
+        for threads in [None, Some(1), Some(3)] {
+            let scan = ScanOptions{threads, ..Default::default()};
+            let roots = bracket_root_many(fx, -1.0, 1000.0, 100_000, scan, None);
+
+            assert_eq!(roots.len(), expect.len());
+            for (root, expect) in roots.iter().zip(&expect) {
+                assert!((root - expect).abs() < 1.0e-12);
+            }
+        }
+
+        let scan = ScanOptions{threads: Some(4), ..Default::default()};
+        assert!(bracket_root_many(fx, 0.5, 1.0, 0, scan, None).is_empty());
+        assert!(bracket_root_many(fx, 0.5, 1.0, -3, scan, None).is_empty());
+    }
+
+    #[test]
+    fn test_newton_system() {
+        struct Test {
+            fx: fn(&[f64]) -> Vec<f64>,
+            jacobian: fn(&[f64]) -> Vec<Vec<f64>>,
+            xo: Vec<f64>,
+            expect: Result<Vec<f64>, RootError>,
+        }
+
+        let tests = vec![
+            Test{
+                // a circle crossing a line
+                fx: |x| {vec![x[0].powf(2.0) + x[1].powf(2.0) - 4.0, x[0] - x[1]]},
+                jacobian: |x| {vec![vec![2.0*x[0], 2.0*x[1]], vec![1.0, -1.0]]},
+                xo: vec![1.0, 0.5],
+                expect: Ok(vec![2.0_f64.sqrt(), 2.0_f64.sqrt()]),
+            },
+            Test{
+                fx: |x| {vec![
+                    3.0*x[0] - (x[1]*x[2]).cos() - 0.5,
+                    x[0].powf(2.0) - 81.0*(x[1] + 0.1).powf(2.0) + x[2].sin() + 1.06,
+                    (-x[0]*x[1]).exp() + 20.0*x[2] + (10.0*std::f64::consts::PI - 3.0)/3.0,
+                ]},
+                jacobian: |x| {vec![
+                    vec![3.0, x[2]*(x[1]*x[2]).sin(), x[1]*(x[1]*x[2]).sin()],
+                    vec![2.0*x[0], -162.0*(x[1] + 0.1), x[2].cos()],
+                    vec![-x[1]*(-x[0]*x[1]).exp(), -x[0]*(-x[0]*x[1]).exp(), 20.0],
+                ]},
+                xo: vec![0.1, 0.1, -0.1],
+                expect: Ok(vec![0.5, 0.0, -std::f64::consts::PI/6.0]),
+            },
+            Test{
+                // plain newton jumps far away on the first step here, the line search keeps it
+                // on track
+                fx: |x| {vec![x[0].atan(), x[1].atan() + x[0]]},
+                jacobian: |x| {vec![vec![1.0/(1.0 + x[0].powf(2.0)), 0.0], vec![1.0, 1.0/(1.0 + x[1].powf(2.0))]]},
+                xo: vec![3.0, -2.0],
+                expect: Ok(vec![0.0, 0.0]),
+            },
+            Test{
+                fx: |x| {vec![x[0] + x[1] - 1.0, 2.0*x[0] + 2.0*x[1] - 3.0]},
+                jacobian: |_| {vec![vec![1.0, 1.0], vec![2.0, 2.0]]},
+                xo: vec![0.0, 0.0],
+                expect: Err(RootError::ZeroDerivative),
+            },
+            Test{
+                // three equations in two unknowns
+                fx: |x| {vec![x[0] - 1.0, x[1] - 2.0, x[0] + x[1] - 3.0]},
+                jacobian: |_| {vec![vec![1.0, 0.0], vec![0.0, 1.0], vec![1.0, 1.0]]},
+                xo: vec![0.0, 0.0],
+                expect: Err(RootError::NotSquare),
+            },
+        ];
+
+        for test in tests {
+            let estimated = try_newton_system(test.fx, &test.xo, None);
+            let analytic = try_newton_system_with_jacobian(test.fx, test.jacobian, &test.xo, None);
+
+            for result in [estimated, analytic] {
+                match (result, &test.expect) {
+                    (Ok(report), Ok(expect)) => {
+                        for (root, expect) in report.root.iter().zip(expect) {
+                            assert!((root - expect).abs() < 1.0e-12);
+                        }
+                        assert!(report.residual.iter().all(|r| r.abs() < 1.0e-12));
+                    },
+                    (result, expect) => assert_eq!(result.map(|r| r.root), expect.clone()),
+                }
+            }
+
+            assert_eq!(newton_system(test.fx, &test.xo, None).is_some(), test.expect.is_ok());
+            assert_eq!(newton_system_with_jacobian(test.fx, test.jacobian, &test.xo, None).is_some(), test.expect.is_ok());
+        }
+    }
+
+    #[test]
+    fn test_broyden_system() {
+        struct Test {
+            fx: fn(&[f64]) -> Vec<f64>,
+            xo: Vec<f64>,
+            expect: Result<Vec<f64>, RootError>,
+        }
+
+        let tests = vec![
+            Test{
+                fx: |x| {vec![x[0].powf(2.0) + x[1].powf(2.0) - 4.0, x[0] - x[1]]},
+                xo: vec![1.0, 0.5],
+                expect: Ok(vec![2.0_f64.sqrt(), 2.0_f64.sqrt()]),
+            },
+            Test{
+                fx: |x| {vec![
+                    3.0*x[0] - (x[1]*x[2]).cos() - 0.5,
+                    x[0].powf(2.0) - 81.0*(x[1] + 0.1).powf(2.0) + x[2].sin() + 1.06,
+                    (-x[0]*x[1]).exp() + 20.0*x[2] + (10.0*std::f64::consts::PI - 3.0)/3.0,
+                ]},
+                xo: vec![0.1, 0.1, -0.1],
+                expect: Ok(vec![0.5, 0.0, -std::f64::consts::PI/6.0]),
+            },
+            Test{
+                // broyden's tridiagonal function, a classic test problem, its root has no closed
+                // form so only the residual is checked
+                fx: |x| {
+                    let n = x.len();
+                    (0..n).map(|i| {
+                        let left = if i > 0 { x[i - 1] } else { 0.0 };
+                        let right = if i + 1 < n { x[i + 1] } else { 0.0 };
+                        (3.0 - 2.0*x[i])*x[i] - left - 2.0*right + 1.0
+                    }).collect()
+                },
+                xo: vec![-1.0; 10],
+                expect: Ok(vec![]),
+            },
+            Test{
+                fx: |x| {vec![x[0] + x[1] - 1.0, 2.0*x[0] + 2.0*x[1] - 3.0]},
+                xo: vec![0.0, 0.0],
+                expect: Err(RootError::ZeroDerivative),
+            },
+            Test{
+                fx: |x| {vec![x[0] - 1.0, x[1] - 2.0, x[0] + x[1] - 3.0]},
+                xo: vec![0.0, 0.0],
+                expect: Err(RootError::NotSquare),
+            },
+        ];
+
+        for test in tests {
+            for update in [BroydenUpdate::Good, BroydenUpdate::Bad] {
+                let result = try_broyden_system(test.fx, &test.xo, update, None);
+                match (result, &test.expect) {
+                    (Ok(report), Ok(expect)) => {
+                        for (root, expect) in report.root.iter().zip(expect) {
+                            assert!((root - expect).abs() < 1.0e-12);
+                        }
+                        assert!(report.residual.iter().all(|r| r.abs() < 1.0e-12));
+
+                        // the whole point, way fewer calls than recomputing the jacobian each time
+                        let newton = try_newton_system(test.fx, &test.xo, None).unwrap();
+                        assert!(report.evaluations < newton.evaluations, "{:?} {} {}", update, report.evaluations, newton.evaluations);
+                    },
+                    (result, expect) => assert_eq!(result.map(|r| r.root), expect.clone()),
+                }
+
+                assert_eq!(broyden_system(test.fx, &test.xo, update, None).is_some(), test.expect.is_ok());
+            }
+        }
+    }
+
+    #[test]
+    fn test_fixed_point() {
+        struct Test {
+            gx: fn(f64) -> f64,
+            xo: f64,
+            expect: [Result<f64, RootError>; 3],
+        }
+
+        let tests = vec![
+            Test{
+                gx: |x| {x.cos()},
+                xo: 1.0,
+                expect: [Ok(0.7390851332151607), Ok(0.7390851332151607), Ok(0.7390851332151607)],
+            },
+            Test{
+                gx: |x| {(-x).exp()},
+                xo: 0.0,
+                expect: [Ok(0.5671432904097838), Ok(0.5671432904097838), Ok(0.5671432904097838)],
+            },
+            Test{
+                // contracts so slowly that the plain iteration runs out of iterations
+                gx: |x| {0.99*x + 0.01},
+                xo: 0.0,
+                expect: [Err(RootError::MaxIterations), Ok(1.0), Ok(1.0)],
+            },
+            Test{
+                // the fixed point at 1 repels the iteration, only steffensen gets there
+                gx: |x| {x.powf(2.0)},
+                xo: 1.2,
+                expect: [Err(RootError::Diverged), Err(RootError::Diverged), Ok(1.0)],
+            },
+        ];
+
+        let accelerations = [FixedPointAcceleration::Plain, FixedPointAcceleration::Aitken, FixedPointAcceleration::Steffensen];
+        let options = SolverOptions{x_abs_tol: 0.0, x_rel_tol: 1.0e-13, ..Default::default()};
+
+        for test in tests {
+            let mut iterations = Vec::new();
+
+            for (acceleration, expect) in accelerations.iter().zip(test.expect) {
+                let result = try_fixed_point(test.gx, test.xo, *acceleration, options);
+                match (result, expect) {
+                    (Ok(report), Ok(expect)) => {
+                        assert!((report.root - expect).abs() < 1.0e-12, "{:?} {}", acceleration, report.root);
+                        assert!(report.residual.abs() < 1.0e-12);
+                        assert_eq!(fixed_point(test.gx, test.xo, *acceleration, options), report.root);
+                        iterations.push(report.iterations);
+                    },
+                    (result, expect) => assert_eq!(result.map(|r| r.root), expect, "{:?}", acceleration),
+                }
+            }
+
+            // the accelerated ones never take more iterations than the plain one
+            assert!(iterations.windows(2).all(|w| w[1] <= w[0]));
+        }
+    }
+
+    #[test]
+    fn test_complex_roots() {
+        struct Test {
+            fx: fn(Complex) -> Complex,
+            zo: Complex,
+            guesses: [f64; 3],
+            expect: Complex,
+        }
+
+        let tests = vec![
+            Test{
+                fx: |z| {z*z + 1.0},
+                zo: Complex::new(1.0, 1.0),
+                guesses: [0.0, 0.5, 1.0],
+                expect: Complex::new(0.0, 1.0),
+            },
+            Test{
+                // the poles of a damped oscillator with zeta = 0.1 and omega = 2
+                fx: |z| {z*z + 0.4*z + 4.0},
+                zo: Complex::new(0.0, -2.0),
+                guesses: [-1.0, 0.0, 1.0],
+                expect: Complex::new(-0.2, -(4.0_f64 - 0.04).sqrt()),
+            },
+            Test{
+                fx: |z| {z.powi(3) - 1.0},
+                zo: Complex::new(-1.0, 1.0),
+                guesses: [-2.0, -1.5, -1.0],
+                expect: Complex::from_polar(1.0, 2.0*std::f64::consts::PI/3.0),
+            },
+            Test{
+                fx: |z| {z.exp() - 2.0},
+                zo: Complex::new(1.0, 1.0),
+                guesses: [0.0, 1.0, 2.0],
+                expect: Complex::new(2.0_f64.ln(), 0.0),
+            },
+        ];
+
+        for test in tests {
+            let [g0, g1, g2] = test.guesses;
+            let muller = try_muller_root(test.fx, g0, g1, g2, None).unwrap();
+            let newton = try_newton_root_complex(test.fx, test.zo, None).unwrap();
+
+            // muller's root depends on the guesses, so it only has to be one of the conjugates
+            let expect = test.expect;
+            assert!((muller.root - expect).abs() < 1.0e-12 || (muller.root - expect.conj()).abs() < 1.0e-12, "{}", muller.root);
+            assert!(muller.residual.abs() < 1.0e-12);
+
+            assert!((newton.root - expect).abs() < 1.0e-12, "{}", newton.root);
+
+            assert_eq!(muller_root(test.fx, g0, g1, g2, None), muller.root);
+            assert_eq!(newton_root_complex(test.fx, test.zo, None), newton.root);
         }
+
+        let result = try_newton_root_complex(|z| z*z + 1.0, Complex::ZERO, None);
+        assert_eq!(result.map(|r| r.root), Err(RootError::ZeroDerivative));
     }
-}
\ No newline at end of file
+}